@@ -1,12 +1,15 @@
-use super::{graph, map};
+pub mod secret_rooms;
+
+use super::{common::XY, graph, map, traversal, Config};
 use map::{
   room,
   room::{Room, RoomType},
   room_templates,
-  room_templates::RoomTemplates,
+  room_templates::{RoomTemplate, RoomTemplates},
 };
 use petgraph::graph::NodeIndex;
-use rand::prelude::{SliceRandom, ThreadRng};
+use rand::{rngs::StdRng, seq::SliceRandom};
+use std::collections::HashMap;
 
 pub fn try_node_recursive(
   node_a: &NodeIndex<u32>,
@@ -14,23 +17,33 @@ pub fn try_node_recursive(
   map: &mut map::Map,
   templates: &mut RoomTemplates,
   chain: &mut Vec<NodeIndex>,
-  rng: &mut ThreadRng,
+  config: &Config,
+  rng: &mut StdRng,
 ) {
   let graph::MapGraph {
-    graph,
     nodes,
     neighbour_map,
+    roles,
+    positions,
+    ..
   } = map_graph;
 
   // debug::print_map(map.tiles.clone(), map.width);
 
   // Should only create on first node
   let mut room_a = map::find_or_create_start_room(map, templates, &node_a.index());
-  let neighbours = room::get_neighbours(&node_a.index(), neighbour_map);
-  let is_detached = chain.len() == 1 && map.rooms.len() > 0 && !map.rooms.contains_key(&node_a.index());
+  let mut neighbours = room::get_neighbours(&node_a.index(), neighbour_map);
+  let is_detached = chain.len() == 1 && !map.rooms.is_empty() && !map.rooms.contains_key(&node_a.index());
+
+  let boss = graph::boss_node(map_graph);
+  let max_door_capacity = templates.max_door_capacity();
 
-  let weights = graph.node_weights().collect::<Vec<&usize>>();
-  // let _weight = weights.get(node_a.index()).unwrap();
+  // Head towards the boss node first, so the way to it is placed before the map fills up. The sort is stable, so
+  // neighbours the same distance away keep their order
+  if let Some(boss) = boss {
+    let distances = graph::node_distances(boss.index(), neighbour_map);
+    neighbours.sort_by_key(|(_, node_b, _)| distances.get(&node_b.index()).cloned().unwrap_or(usize::MAX));
+  }
 
   // Place first room in the middle of the map
   if nodes.first().unwrap().index() == node_a.index() {
@@ -48,72 +61,218 @@ pub fn try_node_recursive(
 
       if is_existing_node {
         println!("Trying existing node: {}", &node_b.index());
-        let mut chain = Vec::from([node_b.clone()]);
-        try_node_recursive(&node_b, map_graph, map, templates, &mut chain, rng);
+        let mut chain = Vec::from([node_b]);
+        try_node_recursive(&node_b, map_graph, map, templates, &mut chain, config, rng);
       }
     }
   }
 
   // For each node
   for (_, node_b, outbound) in &neighbours {
-    let node_b_weight = weights.get(node_b.index()).unwrap();
-    let room_b_type = if node_b_weight == weights.last().unwrap() {
-      RoomType::Boss
-    } else {
-      RoomType::Normal
-    };
+    let room_b_type = if Some(*node_b) == boss { RoomType::Boss } else { RoomType::Normal };
 
     // Stop at an existing node (TODO: try connect rooms?)
     let is_existing_node = map.rooms.contains_key(&node_b.index());
 
     let mut room_added = false;
-    let mut template_idxs: Vec<usize> = room_a.template.valid_combinations.keys().cloned().collect();
-
-    // randomise templates
-    template_idxs.shuffle(rng);
-
-    // Loop through randomised rooms until we find one that can fit into the map
-    while !template_idxs.is_empty() && !room_added && !is_existing_node && !is_detached {
-      let template_b_idx = template_idxs.pop().unwrap();
-      let mut room_b = Room::new(room_templates::get(&template_b_idx, templates));
-
-      // Prefer rooms that are designed to have minimum doors (eg: T shaped rooms = 3 minimum doors)
-      let has_min_doors = room_b.template.min_doors <= (graph.edges(node_b.clone()).count() + 1) as u32;
-      // Select Boss or Normal room based on node weight
-      let room_is_correct_type = room_b.template.room_type == room_b_type;
-
-      // Each room has a precalculated coordinates
-      let mut room_combinations = if room_is_correct_type && has_min_doors {
-        room_a.template.valid_combinations.get(&template_b_idx).unwrap().clone()
-      } else {
-        Vec::new()
-      };
-
-      room_combinations.shuffle(rng);
-
-      for combination in room_combinations {
-        let (_, door_a_type, door_a_xy, door_b_type, door_b_xy) = combination;
-        let mut room_b_aligned = room::align_room_b(&room_a, &mut room_b, combination);
-
-        if map::can_place_room(map, &room_b_aligned, door_b_type) {
-          // Add door references to room
-          // TODO: Traverse all existing rooms and attempt to optimise for more connections and more rooms?
-          room_a.add_door(node_a.index(), node_b.index(), door_a_type, door_a_xy, *outbound);
-          room_b_aligned.add_door(node_b.index(), node_a.index(), door_b_type, door_b_xy, !outbound);
-
-          // update rooms with the new door
-          map::add_or_update_room(map, node_b.index().clone(), room_b_aligned.clone());
-          map::add_or_update_room(map, node_a.index(), room_a.clone());
-          room_added = true;
-          break;
+    // Room B needs a door for every neighbour, unless no template can host that many
+    let node_b_degree = graph::node_degree(node_b.index(), neighbour_map) as u32;
+    let required_doors = node_b_degree.min(max_door_capacity);
+
+    // Special nodes try templates with their role's tags first, then its fallbacks
+    let tag_options = match roles.get(&node_b.index()) {
+      Some(role) => role.tag_options(),
+      None => vec![vec![]],
+    };
+
+    for required_tags in tag_options.iter() {
+      if room_added || is_existing_node || is_detached {
+        break;
+      }
+
+      let mut template_idxs: Vec<usize> = room_a
+        .template
+        .valid_combinations
+        .keys()
+        .filter(|idx| templates.rooms.get(idx).unwrap().matches_tags(required_tags))
+        .cloned()
+        .collect();
+      // Sorted first so the shuffle only depends on the rng, not on hash order
+      template_idxs.sort();
+
+      // randomise templates, favouring heavier weights. Reversed as pop() takes from the end
+      let mut template_idxs = room_templates::weighted_shuffle(template_idxs, templates, rng);
+      template_idxs.reverse();
+
+      // Loop through randomised rooms until we find one that can fit into the map
+      while !template_idxs.is_empty() && !room_added {
+        let template_b_idx = template_idxs.pop().unwrap();
+        let mut room_b = Room::new(room_templates::get(&template_b_idx, templates));
+
+        // Prefer rooms that are designed to have minimum doors (eg: T shaped rooms = 3 minimum doors)
+        let has_min_doors = room_b.template.min_doors <= node_b_degree;
+        // ..and that have enough door faces for every neighbour
+        let has_door_capacity = room_b.template.door_capacity() >= required_doors;
+        // Select Boss or Normal room based on node weight
+        let room_is_correct_type = room_b.template.room_type == room_b_type;
+        // Rare templates can be limited per map, or kept apart from each other
+        let within_limits = within_usage_limits(&room_b.template, node_b.index(), map, neighbour_map);
+
+        // Each room has a precalculated coordinates
+        let mut room_combinations = if room_is_correct_type && has_min_doors && has_door_capacity && within_limits {
+          room_a.template.valid_combinations.get(&template_b_idx).unwrap().clone()
+        } else {
+          Vec::new()
+        };
+
+        room_combinations.shuffle(rng);
+        // Try the combinations that put room B closest to its preferred position first, to the nearest tile. Among
+        // those, doors on the side facing node B's position go first, and the sort keeps ties in their random order
+        let target = target_centre(positions, &room_a, node_a.index(), node_b.index());
+        let direction = preferred_direction(positions, node_a.index(), node_b.index());
+        if let (Some(target), Some(direction)) = (target, direction) {
+          let (w, h) = (room_b.template.w as f64, room_b.template.h as f64);
+          let target_distance = |combination: &room_templates::RoomCombination| {
+            let (x, y) = room::room_b_xy(&room_a, combination);
+            ((x as f64 + w / 2.0 - target.0).powi(2) + (y as f64 + h / 2.0 - target.1).powi(2))
+              .sqrt()
+              .round()
+          };
+          room_combinations.sort_by(|a, b| {
+            target_distance(a)
+              .total_cmp(&target_distance(b))
+              .then(door_alignment(b.1, direction).total_cmp(&door_alignment(a.1, direction)))
+          });
+        }
+
+        for combination in room_combinations {
+          let (_, door_a_type, door_a_xy, door_b_type, door_b_xy) = combination;
+          let mut room_b_aligned = room::align_room_b(&room_a, &mut room_b, combination);
+
+          // Both rooms must keep enough free doors for the neighbours still to be placed
+          let reserves_doors = reserves_doors(&room_a, node_a.index(), node_b.index(), door_a_xy, map, neighbour_map)
+            && reserves_doors(&room_b_aligned, node_b.index(), node_a.index(), door_b_xy, map, neighbour_map);
+
+          // The player must be able to get between the new door and room A's other doors
+          let is_traversable = match &config.physics {
+            Some(physics) => traversal::can_connect_door(&room_a, (door_a_type, door_a_xy), physics),
+            None => true,
+          };
+
+          if reserves_doors && is_traversable && map::can_place_room(map, &room_b_aligned, door_b_type) {
+            // Add door references to room
+            // TODO: Traverse all existing rooms and attempt to optimise for more connections and more rooms?
+            room_a.add_door(node_a.index(), node_b.index(), door_a_type, door_a_xy, *outbound);
+            room_b_aligned.add_door(node_b.index(), node_a.index(), door_b_type, door_b_xy, !outbound);
+
+            // update rooms with the new door
+            map::add_or_update_room(map, node_b.index(), room_b_aligned.clone());
+            map::add_or_update_room(map, node_a.index(), room_a.clone());
+            room_added = true;
+            break;
+          }
         }
       }
     }
 
     // if this random room can be placed, try this room's connecting nodes
     if room_added {
-      chain.push(node_b.clone());
-      try_node_recursive(&node_b, map_graph, map, templates, chain, rng);
+      chain.push(*node_b);
+      try_node_recursive(node_b, map_graph, map, templates, chain, config, rng);
+    }
+  }
+}
+
+/// Where room B's centre should go: as far from room A's centre as node B's position is from node A's. None when
+/// either node has no position, or they share one
+fn target_centre(positions: &HashMap<usize, (f64, f64)>, room_a: &Room, node_a_idx: usize, node_b_idx: usize) -> Option<(f64, f64)> {
+  let (ax, ay) = positions.get(&node_a_idx)?;
+  let (bx, by) = positions.get(&node_b_idx)?;
+  let (dx, dy) = (bx - ax, by - ay);
+
+  if dx.abs() + dy.abs() < 1e-6 {
+    return None;
+  }
+
+  let (centre_x, centre_y) = room_a.bounds().centre();
+  Some((centre_x + dx, centre_y + dy))
+}
+
+/// Unit vector from node A's preferred position towards node B's, if both have one and they differ
+fn preferred_direction(positions: &HashMap<usize, (f64, f64)>, node_a_idx: usize, node_b_idx: usize) -> Option<(f64, f64)> {
+  let (ax, ay) = positions.get(&node_a_idx)?;
+  let (bx, by) = positions.get(&node_b_idx)?;
+  let (dx, dy) = (bx - ax, by - ay);
+  let length = (dx * dx + dy * dy).sqrt();
+
+  if length < 1e-6 {
+    return None;
+  }
+  Some((dx / length, dy / length))
+}
+
+/// How closely a door of room A faces a direction, from 1 (straight at it) to -1 (away from it)
+fn door_alignment(door_type: u8, (dx, dy): (f64, f64)) -> f64 {
+  match door_type {
+    2 => -dy,
+    3 => dx,
+    4 => dy,
+    5 => -dx,
+    _ => 0.0,
+  }
+}
+
+/// Check a template's max rooms per map and minimum distance between repeats
+pub fn within_usage_limits(template: &RoomTemplate, node_idx: usize, map: &map::Map, neighbour_map: &graph::NeighbourMap) -> bool {
+  let placed_nodes: Vec<usize> = map
+    .rooms
+    .iter()
+    .filter(|(idx, room)| room.template.id == template.id && **idx != node_idx)
+    .map(|(idx, _)| *idx)
+    .collect();
+
+  if let Some(max) = template.max_per_map {
+    if placed_nodes.len() as u32 >= max {
+      return false;
+    }
+  }
+
+  if let Some(min_distance) = template.min_repeat_distance {
+    if !placed_nodes.is_empty() {
+      let distances = graph::node_distances(node_idx, neighbour_map);
+      let too_close = placed_nodes
+        .iter()
+        .any(|idx| distances.get(idx).is_some_and(|distance| *distance < min_distance));
+
+      if too_close {
+        return false;
+      }
     }
   }
+
+  true
+}
+
+/// Check a room still has a free door for each neighbour that isn't on the map yet, after using this door
+pub fn reserves_doors(
+  room: &Room,
+  node_idx: usize,
+  connecting_idx: usize,
+  door_xy: XY,
+  map: &map::Map,
+  neighbour_map: &graph::NeighbourMap,
+) -> bool {
+  let mut pending: Vec<usize> = room::get_neighbours(&node_idx, neighbour_map)
+    .iter()
+    .map(|(_, neighbour, _)| neighbour.index())
+    .filter(|idx| *idx != connecting_idx && !map.rooms.contains_key(idx))
+    .collect();
+  pending.sort();
+  pending.dedup();
+
+  if pending.is_empty() {
+    return true;
+  }
+
+  room.free_door_slots_after(door_xy) >= pending.len() as u32
 }
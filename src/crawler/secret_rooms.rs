@@ -0,0 +1,101 @@
+use super::{graph, map, traversal, Config};
+use graph::{MapGraph, NodeRole};
+use map::{
+  room,
+  room::Room,
+  room_templates::{self, RoomTemplates},
+};
+use rand::{rngs::StdRng, seq::SliceRandom};
+use std::collections::HashSet;
+
+/// Hide up to `count` secret rooms behind breakable walls. Each hangs off an existing room through a free door, and
+/// is added to the graph as a node with the "secret" role and an edge from that room. Dead-end rooms are tried first,
+/// and around each room, spots beside a room it has no edge to. The door is a secret door tile (6) on both sides,
+/// which exports as a solid wall
+pub fn place(map_graph: &mut MapGraph, map: &mut map::Map, templates: &mut RoomTemplates, count: usize, config: &Config, rng: &mut StdRng) {
+  let secret_tags = vec!["secret".to_string()];
+
+  // Dead ends first, then every other room, each group in random order
+  let mut hosts: Vec<usize> = map.rooms.keys().cloned().collect();
+  hosts.sort();
+  hosts.shuffle(rng);
+  hosts.sort_by_key(|idx| graph::node_degree(*idx, &map_graph.neighbour_map) != 1);
+
+  let mut placed = 0;
+
+  for host_idx in hosts {
+    if placed == count {
+      break;
+    }
+
+    let mut host = map.rooms.get(&host_idx).unwrap().clone();
+
+    let mut template_idxs: Vec<usize> = host
+      .template
+      .valid_combinations
+      .keys()
+      .filter(|idx| templates.rooms.get(idx).unwrap().matches_tags(&secret_tags))
+      .cloned()
+      .collect();
+    template_idxs.sort();
+    let template_idxs = room_templates::weighted_shuffle(template_idxs, templates, rng);
+
+    'templates: for template_idx in template_idxs {
+      let mut secret_room = Room::new(room_templates::get(&template_idx, templates));
+      let mut combinations = host.template.valid_combinations.get(&template_idx).unwrap().clone();
+      combinations.shuffle(rng);
+      // The sort keeps each group in its random order
+      combinations.sort_by_cached_key(|combination| {
+        let aligned = room::align_room_b(&host, &mut secret_room.clone(), *combination);
+        !beside_unlinked_room(map, &aligned, host_idx, map_graph)
+      });
+
+      for combination in combinations {
+        let (_, host_door_type, host_door_xy, secret_door_type, secret_door_xy) = combination;
+
+        // Only doors the host room hasn't used
+        let host_door_idx = ((host_door_xy.1 - 1) * host.template.w + host_door_xy.0 - 1) as usize;
+        if !(2..=5).contains(&host.tiles[host_door_idx]) {
+          continue;
+        }
+
+        let is_traversable = match &config.physics {
+          Some(physics) => traversal::can_connect_door(&host, (host_door_type, host_door_xy), physics),
+          None => true,
+        };
+
+        let mut secret_room_aligned = room::align_room_b(&host, &mut secret_room, combination);
+
+        if is_traversable && map::can_place_room(map, &secret_room_aligned, secret_door_type) {
+          let secret_idx = map_graph.attach_node(host_idx, 0, NodeRole::new(&["secret"]));
+          host.add_secret_door(host_idx, secret_idx, host_door_type, host_door_xy, true);
+          secret_room_aligned.add_secret_door(secret_idx, host_idx, secret_door_type, secret_door_xy, false);
+
+          map::add_or_update_room(map, secret_idx, secret_room_aligned);
+          map::add_or_update_room(map, host_idx, host);
+
+          placed += 1;
+          break 'templates;
+        }
+      }
+    }
+  }
+}
+
+/// Whether the room would border a placed room, other than the host, that the host has no graph edge to
+fn beside_unlinked_room(map: &map::Map, room: &Room, host_idx: usize, map_graph: &MapGraph) -> bool {
+  let linked: HashSet<usize> = room::get_neighbours(&host_idx, &map_graph.neighbour_map)
+    .iter()
+    .map(|(_, neighbour, _)| neighbour.index())
+    .chain([host_idx])
+    .collect();
+
+  let bounds = room.bounds();
+  let around = (bounds.x - 1..=bounds.x + bounds.w).flat_map(|x| [(x, bounds.y - 1), (x, bounds.y + bounds.h)]);
+  let sides = (bounds.y..bounds.y + bounds.h).flat_map(|y| [(bounds.x - 1, y), (bounds.x + bounds.w, y)]);
+
+  around
+    .chain(sides)
+    .filter_map(|(x, y)| map.room_at(x, y))
+    .any(|idx| !linked.contains(&idx))
+}
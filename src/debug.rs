@@ -33,9 +33,21 @@ pub fn print_map(tiles: Vec<u8>, width: i32) {
     } else if tile == 8 {
       // BG: black
       print!("\x1B[30m🀫\x1B[39m ");
+    } else if tile == 6 {
+      // SECRET DOOR: magenta
+      print!("\x1B[35m🀫\x1B[39m ");
     } else if tile == 7 {
       // DOOR: blue
       print!("\x1B[34m🀫\x1B[39m ");
+    } else if tile == 10 {
+      // PLATFORM: yellow
+      print!("\x1B[33m=\x1B[39m ");
+    } else if tile == 11 {
+      // LADDER: yellow
+      print!("\x1B[33mH\x1B[39m ");
+    } else if tile == 12 {
+      // ONE WAY PLATFORM: yellow
+      print!("\x1B[33m-\x1B[39m ");
     } else {
       // WALL: grey white
       print!("🀕 ");
@@ -44,7 +56,7 @@ pub fn print_map(tiles: Vec<u8>, width: i32) {
     x += 1;
     // end of the row, move down one and back to the left
     if (x + 1) > width {
-      println!("");
+      println!();
       x = 0;
     }
   }
@@ -74,7 +86,7 @@ pub fn print_map_numbers(tiles: Vec<u8>, width: i32) {
     x += 1;
     // end of the row, move down one and back to the left
     if (x + 1) > width {
-      println!("");
+      println!();
       x = 0;
     }
   }
@@ -88,7 +100,7 @@ pub fn print_map_history(history: Vec<Vec<u8>>, width: i32) {
 }
 
 #[allow(dead_code)]
-pub fn print_er_diagram(graph: &StableGraph<usize, usize>, nodes: &Vec<NodeIndex<u32>>) {
+pub fn print_er_diagram(graph: &StableGraph<usize, usize>, nodes: &[NodeIndex<u32>]) {
   println!("erDiagram");
 
   let weights = graph.node_weights().collect::<Vec<&usize>>();
@@ -103,7 +115,7 @@ pub fn print_er_diagram(graph: &StableGraph<usize, usize>, nodes: &Vec<NodeIndex
 }
 
 #[allow(dead_code)]
-pub fn print_plantuml_nodes(graph: &StableGraph<usize, usize>, nodes: &Vec<NodeIndex<u32>>) {
+pub fn print_plantuml_nodes(graph: &StableGraph<usize, usize>, nodes: &[NodeIndex<u32>]) {
   println!("@startuml");
   println!("(*) --> (*)");
 
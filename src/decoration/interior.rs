@@ -0,0 +1,290 @@
+use crate::{
+  common::{xy_idx, XY},
+  map::{layers, room::Room, Map},
+  traversal::{self, JumpPhysics},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::{HashSet, VecDeque};
+
+/// Attempts at collapsing a room before leaving its interior empty
+const MAX_ATTEMPTS: usize = 10;
+
+/// Interior details, written to the map's decoration layer
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Detail {
+  Empty = 0,
+  /// Solid blocks the player can stand on
+  Terrain = 1,
+  /// Spikes, lava etc. Blocks the way the same as terrain
+  Hazard = 2,
+  /// Background props, the player passes through them
+  Decor = 3,
+}
+
+const DETAILS: [Detail; 4] = [Detail::Empty, Detail::Terrain, Detail::Hazard, Detail::Decor];
+
+/// Adjacency rules for wave function collapse
+pub struct RuleSet {
+  /// Relative chance of each detail, in the order of DETAILS
+  pub weights: [u32; 4],
+  /// Whether a detail is allowed directly on top of another. None is the room's solid wall or floor
+  pub above: fn(Detail, Option<Detail>) -> bool,
+  /// Whether two details are allowed side by side
+  pub beside: fn(Detail, Detail) -> bool,
+}
+
+/// Terrain, hazards and decor all need something solid underneath, hazards never touch decor
+fn grounded(upper: Detail, lower: Option<Detail>) -> bool {
+  match upper {
+    Detail::Empty => true,
+    _ => matches!(lower, None | Some(Detail::Terrain)),
+  }
+}
+
+fn no_decor_by_hazards(a: Detail, b: Detail) -> bool {
+  !matches!((a, b), (Detail::Hazard, Detail::Decor) | (Detail::Decor, Detail::Hazard))
+}
+
+/// Pick the rules for a room from its template tags
+pub fn rule_set(tags: &[String]) -> RuleSet {
+  let has_tag = |tag: &str| tags.iter().any(|t| t == tag);
+
+  let weights = if has_tag("shop") || has_tag("save") {
+    // Safe rooms only get decor
+    [12, 0, 0, 3]
+  } else if has_tag("arena") {
+    [14, 2, 3, 1]
+  } else if has_tag("vertical") {
+    [12, 4, 1, 1]
+  } else {
+    [14, 2, 1, 2]
+  };
+
+  RuleSet {
+    weights,
+    above: grounded,
+    beside: no_decor_by_hazards,
+  }
+}
+
+/// Fill every placed room's interior with details using wave function collapse. Door approaches, ladders and platform
+/// landings are kept clear, and any door reachable from another before detailing must still be reachable once terrain
+/// and hazards are in place, otherwise the room is collapsed again
+pub fn fill(map: &mut Map, physics: &JumpPhysics, seed: u64) {
+  let mut node_idxs: Vec<usize> = map.rooms.keys().cloned().collect();
+  node_idxs.sort();
+
+  for node_idx in node_idxs {
+    let room = map.rooms.get(&node_idx).unwrap();
+    let mut rng = StdRng::seed_from_u64(seed.rotate_left(32) ^ node_idx as u64);
+    let rules = rule_set(&room.template.tags);
+
+    let details = (0..MAX_ATTEMPTS)
+      .filter_map(|_| collapse(room, &rules, &mut rng))
+      .find(|details| is_traversable(room, details, physics));
+
+    if let Some(details) = details {
+      for (x, y, detail) in details {
+        let idx = xy_idx(room.x + x, room.y + y, map.width);
+        if idx >= map.layers.decoration.len() {
+          continue;
+        }
+
+        map.layers.decoration[idx] = detail as u8;
+        match detail {
+          Detail::Terrain => map.layers.collision[idx] = layers::SOLID,
+          Detail::Hazard => map.layers.collision[idx] = layers::HAZARD,
+          _ => {}
+        }
+      }
+    }
+  }
+}
+
+/// Room tile coordinates (from 0) and the detail collapsed there
+pub type Details = Vec<(i32, i32, Detail)>;
+
+fn collapse(room: &Room, rules: &RuleSet, rng: &mut StdRng) -> Option<Details> {
+  let w = room.template.w;
+  let h = room.template.h;
+  let protected = protected_tiles(room);
+
+  // Every detail still possible for each tile, None for tiles outside the interior
+  let mut options: Vec<Option<Vec<Detail>>> = room
+    .tiles
+    .iter()
+    .enumerate()
+    .map(|(idx, tile)| {
+      let xy = (idx as i32 % w, idx as i32 / w);
+      match (*tile, protected.contains(&xy)) {
+        (8, true) => Some(vec![Detail::Empty]),
+        (8, false) => Some(DETAILS.to_vec()),
+        _ => None,
+      }
+    })
+    .collect();
+
+  // Constrain the whole interior against the walls before anything collapses
+  let mut queue: VecDeque<XY> = (0..options.len() as i32).map(|idx| (idx % w, idx / w)).collect();
+  if !propagate(&mut options, &mut queue, w, h, rules) {
+    return None;
+  }
+
+  loop {
+    // Collapse the lowest entropy tile next, breaking ties randomly
+    let mut undecided: Vec<(usize, usize)> = options
+      .iter()
+      .enumerate()
+      .filter_map(|(idx, o)| o.as_ref().filter(|o| o.len() > 1).map(|o| (o.len(), idx)))
+      .collect();
+
+    if undecided.is_empty() {
+      break;
+    }
+
+    undecided.shuffle(rng);
+    let (_, idx) = *undecided.iter().min_by_key(|(entropy, _)| *entropy).unwrap();
+
+    let choices = options[idx].clone().unwrap();
+    let total: u32 = choices.iter().map(|d| rules.weights[*d as usize]).sum();
+    let chosen = if total == 0 {
+      Detail::Empty
+    } else {
+      let mut roll = rng.gen_range(0..total);
+      *choices
+        .iter()
+        .find(|d| {
+          let weight = rules.weights[**d as usize];
+          if roll < weight {
+            true
+          } else {
+            roll -= weight;
+            false
+          }
+        })
+        .unwrap()
+    };
+
+    options[idx] = Some(vec![chosen]);
+    queue.push_back((idx as i32 % w, idx as i32 / w));
+
+    if !propagate(&mut options, &mut queue, w, h, rules) {
+      return None;
+    }
+  }
+
+  Some(
+    options
+      .iter()
+      .enumerate()
+      .filter_map(|(idx, o)| o.as_ref().map(|o| (idx as i32 % w, idx as i32 / w, o[0])))
+      .collect(),
+  )
+}
+
+/// Remove options that no longer fit their neighbours, spreading out from each changed tile.
+/// Returns false if a tile runs out of options
+fn propagate(options: &mut [Option<Vec<Detail>>], queue: &mut VecDeque<XY>, w: i32, h: i32, rules: &RuleSet) -> bool {
+  let get = |options: &[Option<Vec<Detail>>], x: i32, y: i32| -> Option<Vec<Detail>> {
+    if x < 0 || y < 0 || x >= w || y >= h {
+      return None;
+    }
+    options[(y * w + x) as usize].clone()
+  };
+
+  while let Some((x, y)) = queue.pop_front() {
+    for (nx, ny) in [(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)] {
+      let idx = (ny * w + nx) as usize;
+      let neighbour = match get(options, nx, ny) {
+        Some(neighbour) => neighbour,
+        None => continue,
+      };
+
+      let lower = get(options, nx, ny + 1);
+      let upper = get(options, nx, ny - 1);
+      let left = get(options, nx - 1, ny);
+      let right = get(options, nx + 1, ny);
+
+      let remaining: Vec<Detail> = neighbour
+        .iter()
+        .cloned()
+        .filter(|detail| {
+          // Walls and doors count as solid ground, and anything can sit beside them
+          let fits_lower = match &lower {
+            Some(lower) => lower.iter().any(|l| (rules.above)(*detail, Some(*l))),
+            None => (rules.above)(*detail, None),
+          };
+          let fits_upper = match &upper {
+            Some(upper) => upper.iter().any(|u| (rules.above)(*u, Some(*detail))),
+            None => true,
+          };
+          let fits_beside = [&left, &right].iter().all(|side| match side {
+            Some(side) => side.iter().any(|s| (rules.beside)(*detail, *s)),
+            None => true,
+          });
+
+          fits_lower && fits_upper && fits_beside
+        })
+        .collect();
+
+      if remaining.is_empty() {
+        return false;
+      }
+
+      if remaining.len() != neighbour.len() {
+        options[idx] = Some(remaining);
+        queue.push_back((nx, ny));
+      }
+    }
+  }
+
+  true
+}
+
+/// Tiles that must stay empty: the approach to each used or secret door, ladders and the tile above each platform
+pub fn protected_tiles(room: &Room) -> HashSet<XY> {
+  let w = room.template.w;
+  let mut protected = HashSet::new();
+
+  for (idx, tile) in room.tiles.iter().enumerate() {
+    let (x, y) = (idx as i32 % w, idx as i32 / w);
+
+    match tile {
+      6 | 7 => {
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+          protected.insert((x + dx, y + dy));
+          protected.insert((x + dx * 2, y + dy * 2));
+        }
+      }
+      11 => {
+        protected.insert((x, y));
+        protected.insert((x, y - 1));
+      }
+      10 | 12 => {
+        protected.insert((x, y - 1));
+      }
+      _ => {}
+    }
+  }
+
+  protected
+}
+
+/// Each door must still reach the doors it could reach without details, with terrain and hazards blocking the way
+pub fn is_traversable(room: &Room, details: &Details, physics: &JumpPhysics) -> bool {
+  let mut tiles = room.tiles.clone();
+
+  for (x, y, detail) in details {
+    if matches!(detail, Detail::Terrain | Detail::Hazard) {
+      tiles[xy_idx(*x, *y, room.template.w)] = 1;
+    }
+  }
+
+  let doors = traversal::used_doors(room);
+
+  doors.iter().all(|door| {
+    let before = traversal::reachable_tiles(&room.tiles, room.template.w, *door, physics);
+    let after = traversal::reachable_tiles(&tiles, room.template.w, *door, physics);
+    doors.iter().all(|(_, xy)| !before.contains(xy) || after.contains(xy))
+  })
+}
@@ -0,0 +1,227 @@
+use crate::{
+  common::{xy_idx, XY},
+  map::{self, grid::Grid, room::Room, Map},
+  traversal::{self, Door, JumpPhysics},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+
+// 10 - platform
+// 11 - ladder
+// 12 - one way platform
+const LADDER: u8 = 11;
+const ONE_WAY_PLATFORM: u8 = 12;
+
+/// Attempts at adding geometry to a room before giving up on it
+const MAX_FIXES: usize = 8;
+
+/// Add platforms and ladders to every placed room, until each used door can be reached from all of the room's other
+/// doors. Each room gets its own rng from the seed, so the result only depends on the seed and the rooms. Returns the
+/// rooms that still have a door out of reach after MAX_FIXES
+pub fn decorate(map: &mut Map, physics: &JumpPhysics, seed: u64) -> Vec<usize> {
+  let mut node_idxs: Vec<usize> = map.rooms.keys().cloned().collect();
+  node_idxs.sort();
+
+  let mut unreachable = vec![];
+
+  for node_idx in node_idxs {
+    let mut room = map.rooms.get(&node_idx).unwrap().clone();
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(node_idx as u64));
+
+    if decorate_room(&mut room, physics, &mut rng) {
+      map::add_or_update_room(map, node_idx, room.clone());
+    }
+    if unreachable_doors(&room, physics).is_some() {
+      unreachable.push(node_idx);
+    }
+  }
+
+  unreachable
+}
+
+/// Returns true if the room was changed
+fn decorate_room(room: &mut Room, physics: &JumpPhysics, rng: &mut StdRng) -> bool {
+  let mut changed = false;
+
+  for _ in 0..MAX_FIXES {
+    let (from, to) = match unreachable_doors(room, physics) {
+      Some(doors) => doors,
+      None => break,
+    };
+
+    let route = if rng.gen_bool(0.5) {
+      staircase(room, to, physics, rng)
+    } else {
+      ladder(room, to)
+    };
+
+    // Fall back to a ladder, which always reaches the floor
+    let route = if route.is_empty() { ladder(room, to) } else { route };
+
+    // Both only lead down from the door. When the way there also needs to cross the room, climb the whole way
+    // between the two doors instead
+    let mut fixed = room.clone();
+    paint(&mut fixed, &route);
+    let route = if reaches(&fixed, from, to, physics) {
+      route
+    } else {
+      ladder_between(room, from, to)
+    };
+
+    if !paint(room, &route) {
+      break;
+    }
+    changed = true;
+  }
+
+  changed
+}
+
+/// Set the route's tiles, returns true if any of them changed
+fn paint(room: &mut Room, route: &[(XY, u8)]) -> bool {
+  let mut changed = false;
+
+  for (xy, tile) in route {
+    let idx = xy_idx(xy.0, xy.1, room.template.w);
+    changed |= room.tiles[idx] != *tile;
+    room.tiles[idx] = *tile;
+  }
+
+  changed
+}
+
+fn reaches(room: &Room, from: Door, (_, to_xy): Door, physics: &JumpPhysics) -> bool {
+  traversal::reachable_tiles(&room.tiles, room.template.w, from, physics).contains(&to_xy)
+}
+
+/// The first pair of used doors where the second can't be reached from the first
+fn unreachable_doors(room: &Room, physics: &JumpPhysics) -> Option<(Door, Door)> {
+  let doors = traversal::used_doors(room);
+
+  doors.iter().find_map(|from| {
+    doors
+      .iter()
+      .find(|to| to.1 != from.1 && !reaches(room, *from, **to, physics))
+      .map(|to| (*from, *to))
+  })
+}
+
+/// The interior tile the player stands in to step through a door, starting at 0
+fn approach_tile(room: &Room, (door_type, (x, y)): Door) -> Option<XY> {
+  let (x, y) = (x - 1, y - 1);
+
+  let (ax, ay) = match door_type {
+    2 => (x, y + 1),
+    3 => (x - 1, y),
+    4 => (x, y - 1),
+    5 => (x + 1, y),
+    _ => return None,
+  };
+
+  if is_interior(tile_at(room, ax, ay)) {
+    Some((ax, ay))
+  } else {
+    None
+  }
+}
+
+fn tile_at(room: &Room, x: i32, y: i32) -> u8 {
+  Grid::new(&room.tiles, room.template.w).get(x, y).unwrap_or(0)
+}
+
+/// Interior space, including what this pass has already added to it
+fn is_interior(tile: u8) -> bool {
+  matches!(tile, 8 | LADDER | ONE_WAY_PLATFORM)
+}
+
+/// A ladder from the door's approach tile down to the floor
+fn ladder(room: &Room, door: Door) -> Vec<(XY, u8)> {
+  let (x, mut y) = match approach_tile(room, door) {
+    Some(xy) => xy,
+    None => return vec![],
+  };
+
+  let mut route = vec![];
+
+  while tile_at(room, x, y) == 8 {
+    route.push(((x, y), LADDER));
+    y += 1;
+  }
+
+  route
+}
+
+/// One way platforms stepping down from the door to the floor, each a jump apart and drifting randomly sideways
+fn staircase(room: &Room, door: Door, physics: &JumpPhysics, rng: &mut StdRng) -> Vec<(XY, u8)> {
+  let (approach_x, approach_y) = match approach_tile(room, door) {
+    Some(xy) => xy,
+    None => return vec![],
+  };
+
+  let mut route = vec![];
+  let (door_type, _) = door;
+
+  // A ledge under a side door, so the player lands beside it rather than falling back down. It is one way, as the
+  // last jump up to the door goes through it
+  if matches!(door_type, 3 | 5) && tile_at(room, approach_x, approach_y + 1) == 8 {
+    route.push(((approach_x, approach_y + 1), ONE_WAY_PLATFORM));
+  }
+
+  // Jump into north doors from below, side doors are walked through from the approach tile
+  let target_y = if door_type == 2 { approach_y - 1 } else { approach_y };
+  let mut x = approach_x;
+  let mut standing_y = target_y + physics.jump_height;
+
+  while tile_at(room, x, standing_y + 1) == 8 {
+    let drift = rng.gen_range(-(physics.jump_width - 1).max(0)..=(physics.jump_width - 1).max(0));
+    let step_x = x + drift;
+
+    // Stay inside the room, otherwise keep to the column above
+    if tile_at(room, step_x, standing_y) == 8 && tile_at(room, step_x, standing_y + 1) == 8 {
+      x = step_x;
+    }
+
+    route.push(((x, standing_y + 1), ONE_WAY_PLATFORM));
+    standing_y += physics.jump_height;
+  }
+
+  route
+}
+
+/// A ladder along the shortest way through the room between the two doors' approach tiles. Ladders can be climbed in
+/// every direction, so the doors can be reached from each other whatever the room's floors are
+fn ladder_between(room: &Room, from: Door, to: Door) -> Vec<(XY, u8)> {
+  let (start, end) = match (approach_tile(room, from), approach_tile(room, to)) {
+    (Some(start), Some(end)) => (start, end),
+    _ => return vec![],
+  };
+
+  let mut previous: HashMap<XY, XY> = HashMap::from([(start, start)]);
+  let mut queue: VecDeque<XY> = VecDeque::from([start]);
+
+  while let Some((x, y)) = queue.pop_front() {
+    if (x, y) == end {
+      break;
+    }
+
+    for next in [(x, y - 1), (x + 1, y), (x, y + 1), (x - 1, y)] {
+      if is_interior(tile_at(room, next.0, next.1)) && !previous.contains_key(&next) {
+        previous.insert(next, (x, y));
+        queue.push_back(next);
+      }
+    }
+  }
+
+  if !previous.contains_key(&end) {
+    return vec![];
+  }
+
+  let mut route = vec![(end, LADDER)];
+  let mut xy = end;
+  while xy != start {
+    xy = previous[&xy];
+    route.push((xy, LADDER));
+  }
+
+  route
+}
@@ -35,13 +35,12 @@ use std::iter;
 ///   graph = retworkx.generators.barbell_graph(4, 2)
 ///   mpl_draw(graph)
 ///
-
 pub fn new(num_mesh_nodes: usize, num_path_nodes: usize) -> GraphResult {
   let mut left_mesh = StableGraph::<usize, usize>::default();
   let mut mesh_nodes: Vec<NodeIndex> = (0..num_mesh_nodes).map(|w| left_mesh.add_node(w)).collect();
   let mut nodelen = mesh_nodes.len();
 
-  for i in 0..nodelen - 1 {
+  for i in 0..nodelen {
     for j in i + 1..nodelen {
       left_mesh.add_edge(mesh_nodes[i], mesh_nodes[j], 0);
     }
@@ -50,7 +49,10 @@ pub fn new(num_mesh_nodes: usize, num_path_nodes: usize) -> GraphResult {
   let right_mesh = left_mesh.clone();
 
   let path_nodes: Vec<NodeIndex> = (0..num_path_nodes).map(|w| left_mesh.add_node(w)).collect();
-  left_mesh.add_edge(NodeIndex::new(nodelen - 1), NodeIndex::new(nodelen), 0);
+  // Without a path, the meshes are joined directly below
+  if !path_nodes.is_empty() {
+    left_mesh.add_edge(NodeIndex::new(nodelen - 1), NodeIndex::new(nodelen), 0);
+  }
 
   nodelen += path_nodes.len();
 
@@ -63,7 +65,7 @@ pub fn new(num_mesh_nodes: usize, num_path_nodes: usize) -> GraphResult {
 
   for node in right_mesh.node_indices() {
     let new_node = &right_mesh[node];
-    mesh_nodes.push(left_mesh.add_node(new_node.clone()));
+    mesh_nodes.push(left_mesh.add_node(*new_node));
   }
 
   left_mesh.add_edge(NodeIndex::new(nodelen - 1), NodeIndex::new(nodelen), 0);
@@ -71,7 +73,7 @@ pub fn new(num_mesh_nodes: usize, num_path_nodes: usize) -> GraphResult {
     let new_source = NodeIndex::new(nodelen + edge.source().index());
     let new_target = NodeIndex::new(nodelen + edge.target().index());
     let weight = edge.weight();
-    left_mesh.add_edge(new_source, new_target, weight.clone());
+    left_mesh.add_edge(new_source, new_target, *weight);
   }
 
   // errors with .concat(), marked as unstable?
@@ -0,0 +1,90 @@
+use super::{boss_node, node_degree, shortest_path, MapGraph, NodeRole};
+
+/// Spacing of save points, shops and teleports along the path from the start room to the boss room
+#[derive(Clone)]
+pub struct SavePointConfig {
+  /// Most rooms the player passes through between two save points
+  pub max_save_distance: usize,
+  /// Put a shop beside every nth save point
+  pub shop_every: usize,
+  /// Make every nth save point a teleport
+  pub teleport_every: usize,
+}
+
+impl Default for SavePointConfig {
+  fn default() -> SavePointConfig {
+    SavePointConfig {
+      max_save_distance: 4,
+      shop_every: 2,
+      teleport_every: 3,
+    }
+  }
+}
+
+/// Nodes from the start node to the boss node, following the fewest edges
+pub fn critical_path(map_graph: &MapGraph) -> Option<Vec<usize>> {
+  let start = map_graph.nodes.first()?.index();
+  let boss = boss_node(map_graph)?.index();
+
+  shortest_path(start, boss, &map_graph.neighbour_map)
+}
+
+/// Give save, shop and teleport roles to nodes along the critical path, so the player never passes through more than
+/// max_save_distance rooms without a save point. Nodes with two or fewer neighbours are preferred, as they fit the
+/// small save rooms
+pub fn place_save_points(map_graph: &mut MapGraph, config: &SavePointConfig) {
+  let path = match critical_path(map_graph) {
+    Some(path) => path,
+    None => return,
+  };
+
+  // The start room counts as the first save point, and the boss is never replaced
+  let mut last_save = 0;
+  let mut saves_placed = 0;
+  let mut position = 1;
+
+  while position < path.len() - 1 {
+    if position - last_save < config.max_save_distance.max(1) {
+      position += 1;
+      continue;
+    }
+
+    // Nodes that already have a role (eg: from the generator) keep it
+    let candidates: Vec<usize> = (last_save + 1..=position)
+      .rev()
+      .filter(|p| !map_graph.roles.contains_key(&path[*p]))
+      .collect();
+    let save_position = match candidates
+      .iter()
+      .find(|p| node_degree(path[**p], &map_graph.neighbour_map) <= 2)
+      .or(candidates.first())
+    {
+      Some(save_position) => *save_position,
+      None => {
+        position += 1;
+        continue;
+      }
+    };
+
+    saves_placed += 1;
+
+    let role = if config.teleport_every > 0 && saves_placed % config.teleport_every == 0 {
+      NodeRole::new(&["teleport"]).with_fallback(&["save"])
+    } else {
+      NodeRole::new(&["save"])
+    };
+    map_graph.set_role(path[save_position], role);
+
+    let shop_position = save_position + 1;
+    if config.shop_every > 0
+      && saves_placed % config.shop_every == 0
+      && shop_position < path.len() - 1
+      && !map_graph.roles.contains_key(&path[shop_position])
+    {
+      map_graph.set_role(path[shop_position], NodeRole::new(&["shop"]));
+    }
+
+    last_save = save_position;
+    position = save_position + 1;
+  }
+}
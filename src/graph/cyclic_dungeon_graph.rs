@@ -0,0 +1,160 @@
+use super::{GraphResult, NodeRole};
+use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableGraph};
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashMap;
+
+/// Room requirements of the generated nodes, keyed by node index
+pub type Roles = HashMap<usize, NodeRole>;
+
+/// Most neighbours a node may end up with once a sub-cycle is inserted beside it
+const MAX_DEGREE: usize = 4;
+
+/// The shape of one cycle, from its entrance to its exit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CyclePattern {
+  /// Two plain routes of similar length
+  TwoPaths,
+  /// A long route, and a single room leading back from the exit to the entrance
+  HiddenShortcut,
+  /// A short route past a dangerous room holding a reward, or a long safe route
+  Gambit,
+  /// The direct route is locked just before the exit, and the key is along the other route
+  LockLoop,
+}
+
+impl CyclePattern {
+  pub const ALL: [CyclePattern; 4] = [
+    CyclePattern::TwoPaths,
+    CyclePattern::HiddenShortcut,
+    CyclePattern::Gambit,
+    CyclePattern::LockLoop,
+  ];
+}
+
+/// Generate a dungeon built from cycles, after Joris Dormans' cyclic dungeon generation.
+///
+/// A main cycle joins the start (node 0) to the goal (the heaviest node) along two routes. Then ``sub_cycles`` times,
+/// an edge is replaced by a smaller cycle between its two nodes, so every edge stays part of a loop. Each cycle takes
+/// a random pattern, and the rooms that pattern needs are returned as roles:
+///
+/// - ``lock`` / ``key`` (falling back to ``treasure``) for lock loops
+/// - ``arena`` and ``treasure`` for gambits
+/// - ``shortcut`` for hidden shortcuts
+///
+/// Role tags without a matching template fall back to any room.
+///
+/// :param int sub_cycles: How many cycles to insert into the main cycle
+/// :param rng: The random number generator to draw from
+///
+/// :returns: The generated graph, and the roles of its special nodes
+///
+pub fn new<R: Rng>(sub_cycles: usize, rng: &mut R) -> (GraphResult, Roles) {
+  let mut dungeon = Dungeon {
+    graph: StableGraph::new(),
+    nodes: vec![],
+    roles: HashMap::new(),
+  };
+
+  let start = dungeon.add_node(None);
+  let goal = dungeon.add_node(None);
+  let pattern = *CyclePattern::ALL.choose(rng).unwrap();
+  dungeon.add_cycle(start, goal, pattern, rng);
+
+  for _ in 0..sub_cycles {
+    // Sorted so the choice only depends on the rng
+    let mut edges: Vec<EdgeIndex> = dungeon
+      .graph
+      .edge_indices()
+      .filter(|edge| {
+        let (a, b) = dungeon.graph.edge_endpoints(*edge).unwrap();
+        dungeon.degree(a) < MAX_DEGREE && dungeon.degree(b) < MAX_DEGREE
+      })
+      .collect();
+    edges.sort();
+
+    let edge = match edges.choose(rng) {
+      Some(edge) => *edge,
+      None => break,
+    };
+
+    let (entrance, exit) = dungeon.graph.edge_endpoints(edge).unwrap();
+    dungeon.graph.remove_edge(edge);
+    let pattern = *CyclePattern::ALL.choose(rng).unwrap();
+    dungeon.add_cycle(entrance, exit, pattern, rng);
+  }
+
+  // Reset node weights to be sequentially heavier, keeping the goal heaviest so it becomes the boss room
+  let num_nodes = dungeon.nodes.len();
+  for (i, nw) in dungeon.graph.node_weights_mut().enumerate() {
+    *nw = i + 1;
+  }
+  *dungeon.graph.node_weight_mut(goal).unwrap() = num_nodes + 1;
+
+  ((dungeon.graph, dungeon.nodes), dungeon.roles)
+}
+
+struct Dungeon {
+  graph: StableGraph<usize, usize>,
+  nodes: Vec<NodeIndex>,
+  roles: Roles,
+}
+
+impl Dungeon {
+  fn add_node(&mut self, role: Option<NodeRole>) -> NodeIndex {
+    let node = self.graph.add_node(0);
+    self.nodes.push(node);
+    if let Some(role) = role {
+      self.roles.insert(node.index(), role);
+    }
+    node
+  }
+
+  fn degree(&self, node: NodeIndex) -> usize {
+    self.graph.neighbors_undirected(node).count()
+  }
+
+  /// Join entrance to exit through the given rooms, in order
+  fn add_route(&mut self, entrance: NodeIndex, exit: NodeIndex, rooms: Vec<Option<NodeRole>>) {
+    let mut previous = entrance;
+    for role in rooms {
+      let node = self.add_node(role);
+      self.graph.add_edge(previous, node, 0);
+      previous = node;
+    }
+    self.graph.add_edge(previous, exit, 0);
+  }
+
+  fn add_cycle<R: Rng>(&mut self, entrance: NodeIndex, exit: NodeIndex, pattern: CyclePattern, rng: &mut R) {
+    let plain = |length: usize| vec![None; length];
+
+    match pattern {
+      CyclePattern::TwoPaths => {
+        self.add_route(entrance, exit, plain(rng.gen_range(1..=3)));
+        self.add_route(entrance, exit, plain(rng.gen_range(1..=3)));
+      }
+      CyclePattern::HiddenShortcut => {
+        self.add_route(entrance, exit, plain(rng.gen_range(3..=4)));
+        // Found from the exit side, leading back to the entrance
+        self.add_route(exit, entrance, vec![Some(NodeRole::new(&["shortcut"]))]);
+      }
+      CyclePattern::Gambit => {
+        self.add_route(entrance, exit, plain(rng.gen_range(2..=3)));
+        self.add_route(
+          entrance,
+          exit,
+          vec![Some(NodeRole::new(&["arena"])), Some(NodeRole::new(&["treasure"]))],
+        );
+      }
+      CyclePattern::LockLoop => {
+        let mut direct = plain(rng.gen_range(0..=1));
+        direct.push(Some(NodeRole::new(&["lock"])));
+        self.add_route(entrance, exit, direct);
+
+        let mut detour = plain(rng.gen_range(2..=3));
+        let key_position = detour.len() / 2;
+        detour[key_position] = Some(NodeRole::new(&["key"]).with_fallback(&["treasure"]));
+        self.add_route(entrance, exit, detour);
+      }
+    }
+  }
+}
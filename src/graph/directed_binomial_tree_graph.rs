@@ -30,7 +30,6 @@ use petgraph::visit::IntoEdgeReferences;
 ///   graph = retworkx.generators.binomial_tree_graph(4)
 ///   mpl_draw(graph)
 ///
-
 pub fn new(order: u32, bidirectional: bool) -> GraphResult {
   let num_nodes = usize::pow(2, order);
   let num_edges = usize::pow(2, order) - 1;
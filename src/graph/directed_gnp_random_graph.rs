@@ -1,6 +1,9 @@
 use super::GraphResult;
 use petgraph::stable_graph::{NodeIndex, StableGraph};
-use rand::distributions::{Distribution, Uniform};
+use rand::{
+  distributions::{Distribution, Uniform},
+  Rng,
+};
 
 /// Return a :math:`G_{np}` directed random graph, also known as an
 /// Erdős-Rényi graph or a binomial graph.
@@ -31,16 +34,10 @@ use rand::distributions::{Distribution, Uniform};
 ///    "Efficient generation of large random networks",
 ///    Phys. Rev. E, 71, 036113, 2005.
 /// .. [2] https://github.com/networkx/networkx/blob/networkx-2.4/networkx/generators/random_graphs.py#L49-L120
-pub fn new(num_nodes: isize, probability: f64) -> GraphResult {
+pub fn new<R: Rng>(num_nodes: isize, probability: f64, rng: &mut R) -> GraphResult {
   if num_nodes <= 0 {
     println!("num_nodes must be > 0");
   }
-  // let mut rng: Pcg64 = match seed {
-  //   Some(seed) => Pcg64::seed_from_u64(seed),
-  //   None => Pcg64::from_entropy(),
-  // };
-
-  let mut rng = rand::thread_rng();
   let mut nodes: Vec<NodeIndex> = Vec::new();
   let mut inner_graph = StableGraph::<usize, usize>::default();
 
@@ -54,7 +51,7 @@ pub fn new(num_nodes: isize, probability: f64) -> GraphResult {
   }
 
   if probability > 0.0 {
-    if (probability - 1.0).abs() < std::f64::EPSILON {
+    if (probability - 1.0).abs() < f64::EPSILON {
       for u in 0..num_nodes {
         for v in 0..num_nodes {
           if u != v {
@@ -72,7 +69,7 @@ pub fn new(num_nodes: isize, probability: f64) -> GraphResult {
 
       let between = Uniform::new(0.0, 1.0);
       while v < num_nodes {
-        let random: f64 = between.sample(&mut rng);
+        let random: f64 = between.sample(rng);
         let lr: f64 = (1.0 - random).ln();
         let ratio: isize = (lr / lp) as isize;
         w = w + 1 + ratio;
@@ -80,13 +77,13 @@ pub fn new(num_nodes: isize, probability: f64) -> GraphResult {
         if v == w {
           w += 1;
         }
+        // past the end of row v, wrap onto the next rows
         while v < num_nodes && num_nodes <= w {
-          w -= v;
+          w -= num_nodes;
           v += 1;
           // avoid self loops
           if v == w {
-            w -= v;
-            v += 1;
+            w += 1;
           }
         }
         if v < num_nodes {
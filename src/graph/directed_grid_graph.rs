@@ -0,0 +1,53 @@
+use super::GraphResult;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+
+/// Generate a directed grid graph. Edges run left to right along each row and
+/// top to bottom along each column.
+///
+/// :param int rows: The number of rows to generate the graph with.
+/// :param int cols: The number of columns to generate the graph with.
+/// :param bool bidirectional: Adds edges in both directions between two nodes
+///     if set to ``True``. Default value is ``False``
+///
+/// :returns: The generated grid graph
+/// :rtype: PyDiGraph
+///
+/// .. jupyter-execute::
+///
+///   import retworkx.generators
+///   from retworkx.visualization import mpl_draw
+///
+///   graph = retworkx.generators.directed_grid_graph(2, 3)
+///   mpl_draw(graph)
+///
+pub fn new(rows: usize, cols: usize, bidirectional: bool) -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let nodes: Vec<NodeIndex> = (0..rows * cols).map(|w| graph.add_node(w)).collect();
+
+  for i in 0..rows {
+    for j in 0..cols {
+      let node = nodes[i * cols + j];
+
+      if j + 1 < cols {
+        graph.add_edge(node, nodes[i * cols + j + 1], 0);
+        if bidirectional {
+          graph.add_edge(nodes[i * cols + j + 1], node, 0);
+        }
+      }
+
+      if i + 1 < rows {
+        graph.add_edge(node, nodes[(i + 1) * cols + j], 0);
+        if bidirectional {
+          graph.add_edge(nodes[(i + 1) * cols + j], node, 0);
+        }
+      }
+    }
+  }
+
+  // Reset node weights to be sequentially heavier
+  for (i, nw) in graph.node_weights_mut().enumerate() {
+    *nw = i + 1;
+  }
+
+  (graph, nodes)
+}
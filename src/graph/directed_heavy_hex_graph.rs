@@ -1,4 +1,4 @@
-use super::{GraphResult, };
+use super::GraphResult;
 use petgraph::stable_graph::{NodeIndex, StableGraph};
 
 /// Generate a directed heavy hex graph. Fig. 2 of
@@ -72,11 +72,10 @@ use petgraph::stable_graph::{NodeIndex, StableGraph};
 ///       os.remove(tmp_path)
 ///   image
 ///
-
 pub fn new(distance: usize, bidirectional: bool) -> GraphResult {
   let mut graph = StableGraph::<usize, usize>::default();
 
-  if distance % 2 == 0 {
+  if distance.is_multiple_of(2) {
     println!("Error: d must be odd");
   }
 
@@ -92,7 +91,7 @@ pub fn new(distance: usize, bidirectional: bool) -> GraphResult {
 
   let nodes_data: Vec<NodeIndex> = (0..num_data).map(|w| graph.add_node(w * 2)).collect();
   let nodes_syndrome: Vec<NodeIndex> = (0..num_syndrome).map(|_| graph.add_node(0)).collect();
-  let nodes_flag: Vec<NodeIndex> = (0..num_flag).map(|w| graph.add_node(w + 1 * 2)).collect();
+  let nodes_flag: Vec<NodeIndex> = (0..num_flag).map(|w| graph.add_node(w + 2)).collect();
 
   // connect data and flags
   for (i, flag_chunk) in nodes_flag.chunks(distance - 1).enumerate() {
@@ -107,7 +106,7 @@ pub fn new(distance: usize, bidirectional: bool) -> GraphResult {
   }
 
   // connect data and syndromes
-  for (i, syndrome_chunk) in nodes_syndrome.chunks((distance + 1) / 2).enumerate() {
+  for (i, syndrome_chunk) in nodes_syndrome.chunks(distance.div_ceil(2)).enumerate() {
     if i % 2 == 0 {
       graph.add_edge(nodes_data[i * distance], syndrome_chunk[0], 0);
       graph.add_edge(nodes_data[(i + 1) * distance], syndrome_chunk[0], 0);
@@ -139,7 +138,7 @@ pub fn new(distance: usize, bidirectional: bool) -> GraphResult {
   }
 
   // connect flag and syndromes
-  for (i, syndrome_chunk) in nodes_syndrome.chunks((distance + 1) / 2).enumerate() {
+  for (i, syndrome_chunk) in nodes_syndrome.chunks(distance.div_ceil(2)).enumerate() {
     if i % 2 == 0 {
       for (j, syndrome) in syndrome_chunk.iter().enumerate() {
         if j != 0 {
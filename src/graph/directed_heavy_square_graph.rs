@@ -40,7 +40,7 @@ use petgraph::stable_graph::{NodeIndex, StableGraph};
 pub fn new(distance: usize, bidirectional: bool) -> GraphResult {
   let mut graph = StableGraph::<usize, usize>::default();
 
-  if distance % 2 == 0 {
+  if distance.is_multiple_of(2) {
     println!("Error: d must be odd");
   }
 
@@ -56,7 +56,7 @@ pub fn new(distance: usize, bidirectional: bool) -> GraphResult {
 
   let nodes_data: Vec<NodeIndex> = (0..num_data).map(|w| graph.add_node(w * 2)).collect();
   let nodes_syndrome: Vec<NodeIndex> = (0..num_syndrome).map(|_| graph.add_node(0)).collect();
-  let nodes_flag: Vec<NodeIndex> = (0..num_flag).map(|w| graph.add_node(w + 1 * 2)).collect();
+  let nodes_flag: Vec<NodeIndex> = (0..num_flag).map(|w| graph.add_node(w + 2)).collect();
 
   // connect data and flags
   for (i, flag_chunk) in nodes_flag.chunks(distance - 1).enumerate() {
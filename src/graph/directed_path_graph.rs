@@ -0,0 +1,38 @@
+use super::GraphResult;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+
+/// Generate a directed path graph.
+///
+/// :param int num_nodes: The number of nodes to generate the graph with.
+/// :param bool bidirectional: Adds edges in both directions between two nodes
+///     if set to ``True``. Default value is ``False``
+///
+/// :returns: The generated path graph
+/// :rtype: PyDiGraph
+///
+/// .. jupyter-execute::
+///
+///   import retworkx.generators
+///   from retworkx.visualization import mpl_draw
+///
+///   graph = retworkx.generators.directed_path_graph(10)
+///   mpl_draw(graph)
+///
+pub fn new(num_nodes: usize, bidirectional: bool) -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let nodes: Vec<NodeIndex> = (0..num_nodes).map(|w| graph.add_node(w)).collect();
+
+  for pair in nodes.windows(2) {
+    graph.add_edge(pair[0], pair[1], 0);
+    if bidirectional {
+      graph.add_edge(pair[1], pair[0], 0);
+    }
+  }
+
+  // Reset node weights to be sequentially heavier
+  for (i, nw) in graph.node_weights_mut().enumerate() {
+    *nw = i + 1;
+  }
+
+  (graph, nodes)
+}
@@ -0,0 +1,47 @@
+use super::GraphResult;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+
+/// Generate a directed star graph. Node 0 is the hub and every other node is
+/// a spoke connected only to it.
+///
+/// :param int num_nodes: The number of nodes to generate the graph with,
+///     including the hub.
+/// :param bool inward: If set ``True`` the nodes will be directed towards the
+///     center node. This parameter is ignored if ``bidirectional`` is set to
+///     ``True``.
+/// :param bool bidirectional: Adds edges in both directions between two nodes
+///     if set to ``True``. Default value is ``False``.
+///
+/// :returns: The generated star graph
+/// :rtype: PyDiGraph
+///
+/// .. jupyter-execute::
+///
+///   import retworkx.generators
+///   from retworkx.visualization import mpl_draw
+///
+///   graph = retworkx.generators.directed_star_graph(10)
+///   mpl_draw(graph)
+///
+pub fn new(num_nodes: usize, inward: bool, bidirectional: bool) -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let nodes: Vec<NodeIndex> = (0..num_nodes).map(|w| graph.add_node(w)).collect();
+
+  for &spoke in nodes.iter().skip(1) {
+    if inward && !bidirectional {
+      graph.add_edge(spoke, nodes[0], 0);
+    } else {
+      graph.add_edge(nodes[0], spoke, 0);
+      if bidirectional {
+        graph.add_edge(spoke, nodes[0], 0);
+      }
+    }
+  }
+
+  // Reset node weights to be sequentially heavier
+  for (i, nw) in graph.node_weights_mut().enumerate() {
+    *nw = i + 1;
+  }
+
+  (graph, nodes)
+}
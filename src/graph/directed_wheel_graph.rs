@@ -0,0 +1,65 @@
+use super::GraphResult;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+
+/// Generate a directed wheel graph. Node 0 is the hub and the remaining nodes
+/// form a cycle around it, each with a spoke back to the hub.
+///
+/// :param int num_nodes: The number of nodes to generate the graph with,
+///     including the hub.
+/// :param bool inward: If set ``True`` the spokes will be directed towards the
+///     center node. This parameter is ignored if ``bidirectional`` is set to
+///     ``True``.
+/// :param bool bidirectional: Adds edges in both directions between two nodes
+///     if set to ``True``. Default value is ``False``.
+///
+/// :returns: The generated wheel graph
+/// :rtype: PyDiGraph
+///
+/// .. jupyter-execute::
+///
+///   import retworkx.generators
+///   from retworkx.visualization import mpl_draw
+///
+///   graph = retworkx.generators.directed_wheel_graph(10)
+///   mpl_draw(graph)
+///
+pub fn new(num_nodes: usize, inward: bool, bidirectional: bool) -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let nodes: Vec<NodeIndex> = (0..num_nodes).map(|w| graph.add_node(w)).collect();
+
+  // Spokes
+  for &spoke in nodes.iter().skip(1) {
+    if inward && !bidirectional {
+      graph.add_edge(spoke, nodes[0], 0);
+    } else {
+      graph.add_edge(nodes[0], spoke, 0);
+      if bidirectional {
+        graph.add_edge(spoke, nodes[0], 0);
+      }
+    }
+  }
+
+  // Rim
+  let rim = nodes.get(1..).unwrap_or_default();
+  for pair in rim.windows(2) {
+    graph.add_edge(pair[0], pair[1], 0);
+    if bidirectional {
+      graph.add_edge(pair[1], pair[0], 0);
+    }
+  }
+
+  if rim.len() > 2 {
+    let last = rim[rim.len() - 1];
+    graph.add_edge(last, rim[0], 0);
+    if bidirectional {
+      graph.add_edge(rim[0], last, 0);
+    }
+  }
+
+  // Reset node weights to be sequentially heavier
+  for (i, nw) in graph.node_weights_mut().enumerate() {
+    *nw = i + 1;
+  }
+
+  (graph, nodes)
+}
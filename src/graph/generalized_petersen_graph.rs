@@ -16,7 +16,6 @@ use petgraph::stable_graph::{NodeIndex, StableGraph};
 ///     calls which would create a parallel edge will update the existing edge.
 ///
 /// :returns: The generated generalized Petersen graph.
-
 pub fn new(num_nodes: usize, shift: usize) -> GraphResult {
   if num_nodes < 3 {
     println!("num_nodes must be at least 3");
@@ -28,9 +27,9 @@ pub fn new(num_nodes: usize, shift: usize) -> GraphResult {
 
   let mut graph = StableGraph::<usize, usize>::with_capacity(2 * num_nodes, 3 * num_nodes);
 
-  let star_nodes: Vec<NodeIndex> = (0..num_nodes).map(|w| graph.add_node(w * 2)).collect();
+  let star_nodes: Vec<NodeIndex> = (0..num_nodes).map(|w| graph.add_node(w)).collect();
 
-  let polygon_nodes: Vec<NodeIndex> = (0..num_nodes).map(|w| graph.add_node(w + 1 * 2)).collect();
+  let polygon_nodes: Vec<NodeIndex> = (0..num_nodes).map(|w| graph.add_node(num_nodes + w)).collect();
 
   for i in 0..num_nodes {
     graph.add_edge(star_nodes[i], star_nodes[(i + shift) % num_nodes], 0);
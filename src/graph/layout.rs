@@ -0,0 +1,156 @@
+use super::{planarity, undirected_edges, MapGraph};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Rounds of force simulation for the force-directed layout
+const FORCE_ITERATIONS: usize = 300;
+
+/// Preferred node positions worked out before rooms are placed, so the map takes on the graph's shape
+#[derive(Clone)]
+pub struct LayoutConfig {
+  pub method: Layout,
+  /// Map tiles between the centres of neighbouring rooms the layout aims for
+  pub edge_length: f64,
+}
+
+impl Default for LayoutConfig {
+  fn default() -> LayoutConfig {
+    LayoutConfig {
+      method: Layout::Auto,
+      edge_length: 10.0,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+  /// Planar for graphs that can be drawn without crossings, force-directed for the rest
+  Auto,
+  /// Tutte embedding of the graph's planar part. Edges that would cross are reported as needing portals
+  Planar,
+  /// Fruchterman-Reingold: neighbours pull together and every pair of nodes pushes apart
+  ForceDirected,
+}
+
+/// Fill in the map graph's node positions, in map tiles
+pub fn apply(map_graph: &mut MapGraph, config: &LayoutConfig) {
+  let method = match config.method {
+    Layout::Auto if planarity::is_planar(&map_graph.graph) => Layout::Planar,
+    Layout::Auto => Layout::ForceDirected,
+    method => method,
+  };
+
+  let positions = match method {
+    Layout::Auto | Layout::Planar => {
+      let report = planarity::analyse(map_graph);
+      if !report.is_planar() {
+        println!("planarity: {}", report);
+      }
+      report.positions
+    }
+    Layout::ForceDirected => force_directed(map_graph),
+  };
+
+  map_graph.positions = scale(positions, &undirected_edges(&map_graph.graph), config.edge_length);
+}
+
+/// Resize the positions so the average edge is edge_length long
+fn scale(positions: HashMap<usize, (f64, f64)>, edges: &[(usize, usize)], edge_length: f64) -> HashMap<usize, (f64, f64)> {
+  let lengths: Vec<f64> = edges
+    .iter()
+    .filter_map(|(a, b)| Some(distance(*positions.get(a)?, *positions.get(b)?)))
+    .filter(|length| *length > 1e-9)
+    .collect();
+
+  if lengths.is_empty() {
+    return positions;
+  }
+
+  let factor = edge_length * lengths.len() as f64 / lengths.iter().sum::<f64>();
+  positions
+    .into_iter()
+    .map(|(node, (x, y))| (node, (x * factor, y * factor)))
+    .collect()
+}
+
+fn distance((ax, ay): (f64, f64), (bx, by): (f64, f64)) -> f64 {
+  ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt()
+}
+
+/// Fruchterman and Reingold's spring layout with an ideal edge length of 1. Nodes start evenly around a circle in node
+/// order, so the same graph always gets the same layout
+pub fn force_directed(map_graph: &MapGraph) -> HashMap<usize, (f64, f64)> {
+  let nodes: Vec<usize> = map_graph.nodes.iter().map(|node| node.index()).collect();
+  let edges = undirected_edges(&map_graph.graph);
+  let count = nodes.len();
+  if count == 0 {
+    return HashMap::new();
+  }
+
+  let radius = (count as f64).sqrt();
+  let mut positions: Vec<(f64, f64)> = (0..count)
+    .map(|i| {
+      let angle = 2.0 * PI * i as f64 / count as f64;
+      (radius * angle.cos(), radius * angle.sin())
+    })
+    .collect();
+  let slot: HashMap<usize, usize> = nodes.iter().enumerate().map(|(i, node)| (*node, i)).collect();
+
+  // Each round moves nodes at most this far, cooling to nothing by the last round
+  let start_temperature = radius / 2.0;
+
+  for iteration in 0..FORCE_ITERATIONS {
+    let mut forces: Vec<(f64, f64)> = vec![(0.0, 0.0); count];
+
+    for a in 0..count {
+      for b in a + 1..count {
+        let (dx, dy, length) = offset(positions[a], positions[b], a);
+        // Repulsion k² / d
+        let push = 1.0 / length;
+        forces[a].0 -= dx / length * push;
+        forces[a].1 -= dy / length * push;
+        forces[b].0 += dx / length * push;
+        forces[b].1 += dy / length * push;
+      }
+    }
+
+    for (a, b) in edges.iter() {
+      let (a, b) = match (slot.get(a), slot.get(b)) {
+        (Some(a), Some(b)) => (*a, *b),
+        _ => continue,
+      };
+      let (dx, dy, length) = offset(positions[a], positions[b], a);
+      // Attraction d² / k
+      let pull = length * length;
+      forces[a].0 += dx / length * pull;
+      forces[a].1 += dy / length * pull;
+      forces[b].0 -= dx / length * pull;
+      forces[b].1 -= dy / length * pull;
+    }
+
+    let temperature = start_temperature * (1.0 - iteration as f64 / FORCE_ITERATIONS as f64);
+    for (position, (fx, fy)) in positions.iter_mut().zip(forces) {
+      let strength = (fx * fx + fy * fy).sqrt();
+      if strength > 1e-9 {
+        let step = strength.min(temperature);
+        position.0 += fx / strength * step;
+        position.1 += fy / strength * step;
+      }
+    }
+  }
+
+  nodes.into_iter().zip(positions).collect()
+}
+
+/// Vector from a to b and its length. Nodes on top of each other are nudged apart in a direction picked by the node,
+/// so they can separate
+fn offset(a: (f64, f64), b: (f64, f64), seed: usize) -> (f64, f64, f64) {
+  let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+  let length = (dx * dx + dy * dy).sqrt();
+
+  if length < 1e-9 {
+    let angle = seed as f64;
+    return (angle.cos() * 1e-3, angle.sin() * 1e-3, 1e-3);
+  }
+  (dx, dy, length)
+}
@@ -0,0 +1,47 @@
+use super::GraphResult;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+
+/// Generate an undirected lollipop graph where a mesh graph is connected to a
+/// path.
+///
+/// If ``num_path_nodes`` is ``0`` then this is equivalent to a mesh graph.
+///
+/// :param int num_mesh_nodes: The number of nodes to generate the mesh graph
+///     with.
+/// :param int num_path_nodes: The number of nodes to generate the path
+///     with.
+///
+/// :returns: The generated lollipop graph
+/// :rtype: PyGraph
+///
+/// .. jupyter-execute::
+///
+///   import retworkx.generators
+///   from retworkx.visualization import mpl_draw
+///
+///   graph = retworkx.generators.lollipop_graph(4, 2)
+///   mpl_draw(graph)
+///
+pub fn new(num_mesh_nodes: usize, num_path_nodes: usize) -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let nodes: Vec<NodeIndex> = (0..num_mesh_nodes + num_path_nodes).map(|w| graph.add_node(w)).collect();
+
+  for i in 0..num_mesh_nodes {
+    for j in i + 1..num_mesh_nodes {
+      graph.add_edge(nodes[i], nodes[j], 0);
+    }
+  }
+
+  // The path hangs off the last mesh node, so the first edge joins the two
+  let path_start = num_mesh_nodes.saturating_sub(1);
+  for pair in nodes[path_start..].windows(2) {
+    graph.add_edge(pair[0], pair[1], 0);
+  }
+
+  // Reset node weights to be sequentially heavier
+  for (i, nw) in graph.node_weights_mut().enumerate() {
+    *nw = i + 1;
+  }
+
+  (graph, nodes)
+}
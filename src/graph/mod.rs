@@ -1,28 +1,108 @@
 pub mod barbell_graph;
+pub mod critical_path;
+pub mod cyclic_dungeon_graph;
 pub mod directed_binomial_tree_graph;
+pub mod directed_cycle_graph;
 pub mod directed_gnp_random_graph;
+pub mod directed_grid_graph;
 pub mod directed_heavy_hex_graph;
 pub mod directed_heavy_square_graph;
 pub mod directed_hexagonal_lattice_graph;
+pub mod directed_path_graph;
+pub mod directed_star_graph;
+pub mod directed_wheel_graph;
 pub mod generalized_petersen_graph;
+pub mod layout;
+pub mod lollipop_graph;
+pub mod planarity;
+pub mod postprocess;
 pub mod random_matrix;
+pub mod random_spanning_tree_graph;
+pub mod random_tree_graph;
 use petgraph::stable_graph::{NodeIndex, StableGraph};
 use rand::Rng;
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, VecDeque};
 
 pub type NeighbourMap = HashMap<usize, Vec<(NodeIndex<u32>, NodeIndex<u32>)>>;
 pub type GraphResult = (StableGraph<usize, usize>, Vec<NodeIndex<u32>>);
 
+#[derive(Clone)]
 pub struct MapGraph {
   pub graph: StableGraph<usize, usize>,
   pub nodes: Vec<NodeIndex<u32>>,
   pub neighbour_map: NeighbourMap,
+  /// Room requirements of special nodes, keyed by node index
+  pub roles: HashMap<usize, NodeRole>,
+  /// Where each node would like to be relative to the others in map tiles, keyed by node index. Rooms are placed as
+  /// close as they fit to the same offset from their neighbour, empty leaves placement random
+  pub positions: HashMap<usize, (f64, f64)>,
 }
 
-pub fn random_graph() -> MapGraph {
-  let mut rng = rand::thread_rng();
-  let selection: u32 = rng.gen_range(1..8);
+impl MapGraph {
+  /// A map graph for a generator's output, with no roles or positions yet
+  pub fn new((graph, nodes): GraphResult) -> MapGraph {
+    let neighbour_map = create_neighbour_map((graph.clone(), nodes.clone()));
+
+    MapGraph {
+      graph,
+      nodes,
+      neighbour_map,
+      roles: HashMap::new(),
+      positions: HashMap::new(),
+    }
+  }
+
+  pub fn set_role(&mut self, node_idx: usize, role: NodeRole) {
+    self.roles.insert(node_idx, role);
+  }
+
+  /// Add a node with a role, joined by an edge from an existing node. Returns the new node's index
+  pub fn attach_node(&mut self, from_idx: usize, weight: usize, role: NodeRole) -> usize {
+    let node = self.graph.add_node(weight);
+    self.graph.add_edge(NodeIndex::new(from_idx), node, 0);
+    self.nodes.push(node);
+    self.set_role(node.index(), role);
+    self.neighbour_map = create_neighbour_map((self.graph.clone(), self.nodes.clone()));
+    node.index()
+  }
+}
+
+/// The kind of room a node needs, matched against room template tags
+#[derive(Clone, Default)]
+pub struct NodeRole {
+  /// Tags the room template must have
+  pub tags: Vec<String>,
+  /// Alternative tag sets, tried in order when no template with the preferred tags fits
+  pub fallbacks: Vec<Vec<String>>,
+}
+
+impl NodeRole {
+  pub fn new(tags: &[&str]) -> NodeRole {
+    NodeRole {
+      tags: tags.iter().map(|tag| tag.to_string()).collect(),
+      fallbacks: vec![],
+    }
+  }
+
+  pub fn with_fallback(mut self, tags: &[&str]) -> NodeRole {
+    self.fallbacks.push(tags.iter().map(|tag| tag.to_string()).collect());
+    self
+  }
+
+  /// Every tag set to try in order. An untagged room is the last resort, so the node (and anything past it) still
+  /// makes it onto the map
+  pub fn tag_options(&self) -> Vec<Vec<String>> {
+    let mut options = vec![self.tags.clone()];
+    options.extend(self.fallbacks.iter().cloned());
+    options.push(vec![]);
+    options
+  }
+}
+
+pub fn random_graph<R: Rng>(rng: &mut R) -> MapGraph {
+  let selection: u32 = rng.gen_range(1..17);
   // let selection: u32 = 5;
+  let mut roles = HashMap::new();
 
   let (graph, nodes) = match selection {
     1 => {
@@ -40,12 +120,12 @@ pub fn random_graph() -> MapGraph {
       let num_nodes: isize = rng.gen_range(8..30);
       let probability: f64 = rng.gen_range(0.1..0.3);
       println!("directed_gnp_random_graph");
-      directed_gnp_random_graph::new(num_nodes, probability)
+      directed_gnp_random_graph::new(num_nodes, probability, rng)
     }
     4 => {
       let mut distance: usize = rng.gen_range(3..10);
 
-      if distance % 2 == 0 {
+      if distance.is_multiple_of(2) {
         distance -= 1; // force odd number
       }
 
@@ -55,7 +135,7 @@ pub fn random_graph() -> MapGraph {
     5 => {
       let mut distance: usize = rng.gen_range(3..6);
 
-      if distance % 2 == 0 {
+      if distance.is_multiple_of(2) {
         distance -= 1; // force odd number
       }
 
@@ -74,19 +154,72 @@ pub fn random_graph() -> MapGraph {
       println!("generalized_petersen_graph - nodes:{} shift:{}", num, shift);
       generalized_petersen_graph::new(num, shift)
     }
+    8 => {
+      let num_nodes: usize = rng.gen_range(6..15);
+      println!("directed_path_graph - nodes:{}", num_nodes);
+      directed_path_graph::new(num_nodes, false)
+    }
+    9 => {
+      let num_nodes: usize = rng.gen_range(6..15);
+      println!("directed_cycle_graph - nodes:{}", num_nodes);
+      directed_cycle_graph::new(num_nodes, false)
+    }
+    10 => {
+      // Keep the hub within what a single room can hold doors for
+      let num_nodes: usize = rng.gen_range(4..8);
+      println!("directed_star_graph - nodes:{}", num_nodes);
+      directed_star_graph::new(num_nodes, false, false)
+    }
+    11 => {
+      let num_nodes: usize = rng.gen_range(5..9);
+      println!("directed_wheel_graph - nodes:{}", num_nodes);
+      directed_wheel_graph::new(num_nodes, false, false)
+    }
+    12 => {
+      let mesh_nodes: usize = rng.gen_range(3..6);
+      let path_nodes: usize = rng.gen_range(4..10);
+      println!("lollipop_graph - mesh_nodes: {} path_nodes: {}", mesh_nodes, path_nodes);
+      lollipop_graph::new(mesh_nodes, path_nodes)
+    }
+    13 => {
+      let rows: usize = rng.gen_range(2..5);
+      let cols: usize = rng.gen_range(3..6);
+      println!("directed_grid_graph - rows:{} cols:{}", rows, cols);
+      directed_grid_graph::new(rows, cols, false)
+    }
+    14 => {
+      let num_nodes: usize = rng.gen_range(8..20);
+      let dead_end_ratio: f64 = rng.gen_range(0.1..0.5);
+      println!("random_tree_graph - nodes:{} dead_end_ratio:{:.2}", num_nodes, dead_end_ratio);
+      random_tree_graph::new(num_nodes, 6, 1..=3, dead_end_ratio, rng)
+    }
+    15 => {
+      let rows: usize = rng.gen_range(3..5);
+      let cols: usize = rng.gen_range(3..6);
+      let extra_edges: usize = rng.gen_range(0..4);
+      println!(
+        "random_spanning_tree_graph - grid rows:{} cols:{} extra_edges:{}",
+        rows, cols, extra_edges
+      );
+      let grid = directed_grid_graph::new(rows, cols, false);
+      random_spanning_tree_graph::new(grid, extra_edges, rng)
+    }
+    16 => {
+      let sub_cycles: usize = rng.gen_range(1..4);
+      println!("cyclic_dungeon_graph - sub_cycles:{}", sub_cycles);
+      let (graph, cycle_roles) = cyclic_dungeon_graph::new(sub_cycles, rng);
+      roles = cycle_roles;
+      graph
+    }
     _ => {
       println!("random_matrix");
-      random_matrix::new()
+      random_matrix::new(rng)
     }
   };
 
-  let neighbour_map = create_neighbour_map((graph.clone(), nodes.clone()));
-
-  MapGraph {
-    graph,
-    nodes,
-    neighbour_map,
-  }
+  let mut map_graph = MapGraph::new((graph, nodes));
+  map_graph.roles = roles;
+  map_graph
 }
 
 /// For each node, store the directional neighbours (incoming and outgoing)
@@ -113,3 +246,98 @@ fn create_neighbour_map((graph, nodes): GraphResult) -> NeighbourMap {
 
   neighbour_map
 }
+
+/// Each edge once as (smaller node index, larger node index), ignoring direction, self-loops and parallel edges
+pub(crate) fn undirected_edges(graph: &StableGraph<usize, usize>) -> Vec<(usize, usize)> {
+  let edges: BTreeSet<(usize, usize)> = graph
+    .edge_indices()
+    .filter_map(|edge| graph.edge_endpoints(edge))
+    .filter(|(a, b)| a != b)
+    .map(|(a, b)| (a.index().min(b.index()), a.index().max(b.index())))
+    .collect();
+
+  edges.into_iter().collect()
+}
+
+/// The node that becomes the boss room: the heaviest node, or the last one added of those tied for heaviest
+pub fn boss_node(map_graph: &MapGraph) -> Option<NodeIndex<u32>> {
+  map_graph.graph.node_indices().max_by_key(|node| map_graph.graph[*node])
+}
+
+/// Number of distinct neighbours of a node, incoming and outgoing
+pub fn node_degree(node_idx: usize, neighbour_map: &NeighbourMap) -> usize {
+  let mut neighbours: Vec<usize> = neighbour_map
+    .get(&node_idx)
+    .unwrap_or(&Vec::new())
+    .iter()
+    .map(|(node_a, node_b)| {
+      if node_a.index() == node_idx {
+        node_b.index()
+      } else {
+        node_a.index()
+      }
+    })
+    .collect();
+
+  neighbours.sort();
+  neighbours.dedup();
+  neighbours.len()
+}
+
+/// Breadth first search outwards from a node, ignoring edge direction. Returns the distance to every reachable node
+pub fn node_distances(node_idx: usize, neighbour_map: &NeighbourMap) -> HashMap<usize, usize> {
+  let mut distances: HashMap<usize, usize> = HashMap::from([(node_idx, 0)]);
+  let mut queue: VecDeque<usize> = VecDeque::from([node_idx]);
+
+  while let Some(current) = queue.pop_front() {
+    let distance = distances[&current];
+
+    for (node_a, node_b) in neighbour_map.get(&current).unwrap_or(&Vec::new()) {
+      let next = if node_a.index() == current {
+        node_b.index()
+      } else {
+        node_a.index()
+      };
+
+      if let Entry::Vacant(entry) = distances.entry(next) {
+        entry.insert(distance + 1);
+        queue.push_back(next);
+      }
+    }
+  }
+
+  distances
+}
+
+/// Fewest edges between two nodes, ignoring edge direction. Includes both nodes
+pub fn shortest_path(from: usize, to: usize, neighbour_map: &NeighbourMap) -> Option<Vec<usize>> {
+  let mut previous: HashMap<usize, usize> = HashMap::new();
+  let mut queue: VecDeque<usize> = VecDeque::from([from]);
+  previous.insert(from, from);
+
+  while let Some(current) = queue.pop_front() {
+    if current == to {
+      let mut path = vec![to];
+      while *path.last().unwrap() != from {
+        path.push(previous[path.last().unwrap()]);
+      }
+      path.reverse();
+      return Some(path);
+    }
+
+    for (node_a, node_b) in neighbour_map.get(&current).unwrap_or(&Vec::new()) {
+      let next = if node_a.index() == current {
+        node_b.index()
+      } else {
+        node_a.index()
+      };
+
+      if let Entry::Vacant(entry) = previous.entry(next) {
+        entry.insert(current);
+        queue.push_back(next);
+      }
+    }
+  }
+
+  None
+}
@@ -0,0 +1,542 @@
+use super::{undirected_edges, MapGraph};
+use petgraph::stable_graph::StableGraph;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::f64::consts::PI;
+use std::fmt;
+
+/// An edge ignoring direction, smaller node index first
+type Edge = (usize, usize);
+type Adjacency = BTreeMap<usize, Vec<usize>>;
+/// A face of a drawing, as the nodes around its boundary in order
+type Face = Vec<usize>;
+
+/// Rounds of barycentre averaging for the Tutte embedding
+const TUTTE_ITERATIONS: usize = 500;
+
+/// Whether a graph can be drawn on a plane without edges crossing, and where to draw it
+pub struct PlanarityReport {
+  /// Edges left out so the rest of the graph has no crossings. Their rooms need a portal or a long corridor between
+  /// them, as they can't be placed side by side
+  pub portal_edges: Vec<Edge>,
+  /// Tutte embedding of the graph without the portal edges: the longest face is pinned around a unit circle and every
+  /// other node of its block sits at the average position of its neighbours. Parts outside that block fan out from it
+  /// as trees
+  pub positions: HashMap<usize, (f64, f64)>,
+}
+
+impl PlanarityReport {
+  pub fn is_planar(&self) -> bool {
+    self.portal_edges.is_empty()
+  }
+}
+
+impl fmt::Display for PlanarityReport {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.is_planar() {
+      return write!(f, "planar");
+    }
+
+    let edges: Vec<String> = self.portal_edges.iter().map(|(a, b)| format!("{}-{}", a, b)).collect();
+    write!(
+      f,
+      "not planar, {} edges need portals or corridors: {}",
+      edges.len(),
+      edges.join(", ")
+    )
+  }
+}
+
+/// Check the graph for planarity, pick out the edges that would cross, and lay out what's left
+pub fn analyse(map_graph: &MapGraph) -> PlanarityReport {
+  let edges = undirected_edges(&map_graph.graph);
+  let portal_edges = if is_planar(&map_graph.graph) {
+    vec![]
+  } else {
+    portal_edges(&edges)
+  };
+  let planar_edges: Vec<Edge> = edges.into_iter().filter(|edge| !portal_edges.contains(edge)).collect();
+
+  let mut positions = tutte_positions(&planar_edges);
+  // Nodes without any edges still get a position, in the middle
+  for node in map_graph.nodes.iter() {
+    positions.entry(node.index()).or_insert((0.0, 0.0));
+  }
+
+  PlanarityReport { portal_edges, positions }
+}
+
+/// Whether the graph can be drawn on a plane without edges crossing. Edge direction, self-loops and parallel edges
+/// make no difference
+pub fn is_planar(graph: &StableGraph<usize, usize>) -> bool {
+  embed(&undirected_edges(graph)).is_some()
+}
+
+fn adjacency(edges: &[Edge]) -> Adjacency {
+  let mut adjacency: Adjacency = BTreeMap::new();
+  for (a, b) in edges {
+    adjacency.entry(*a).or_default().push(*b);
+    adjacency.entry(*b).or_default().push(*a);
+  }
+  adjacency
+}
+
+/// Edges to leave out so the rest is planar. A spanning forest never crosses itself, so it goes in first, then every
+/// other edge is kept unless it makes the graph non-planar. Not always the fewest edges, but never more than needed
+/// for the edges kept
+fn portal_edges(edges: &[Edge]) -> Vec<Edge> {
+  let adjacency = adjacency(edges);
+  let mut seen: HashSet<usize> = HashSet::new();
+  let mut kept: Vec<Edge> = vec![];
+
+  for root in adjacency.keys() {
+    if !seen.insert(*root) {
+      continue;
+    }
+    let mut queue = VecDeque::from([*root]);
+    while let Some(node) = queue.pop_front() {
+      for next in &adjacency[&node] {
+        if seen.insert(*next) {
+          kept.push((node.min(*next), node.max(*next)));
+          queue.push_back(*next);
+        }
+      }
+    }
+  }
+
+  let tree: HashSet<Edge> = kept.iter().cloned().collect();
+  let mut portals = vec![];
+
+  for edge in edges.iter().filter(|edge| !tree.contains(edge)) {
+    kept.push(*edge);
+    if embed(&kept).is_none() {
+      kept.pop();
+      portals.push(*edge);
+    }
+  }
+
+  portals
+}
+
+/// Faces of every biconnected block of the graph, or None when a block has no planar drawing
+fn embed(edges: &[Edge]) -> Option<Vec<Vec<Face>>> {
+  blocks(edges).iter().map(|block| embed_block(block)).collect()
+}
+
+/// Split the edges into biconnected blocks (Hopcroft-Tarjan). A graph is planar exactly when all of its blocks are
+fn blocks(edges: &[Edge]) -> Vec<Vec<Edge>> {
+  struct Search {
+    adjacency: Adjacency,
+    discovered: HashMap<usize, usize>,
+    low: HashMap<usize, usize>,
+    stack: Vec<Edge>,
+    blocks: Vec<Vec<Edge>>,
+  }
+
+  fn visit(search: &mut Search, node: usize, parent: Option<usize>) {
+    let order = search.discovered.len();
+    search.discovered.insert(node, order);
+    search.low.insert(node, order);
+
+    for next in search.adjacency[&node].clone() {
+      if !search.discovered.contains_key(&next) {
+        search.stack.push((node, next));
+        visit(search, next, Some(node));
+        search.low.insert(node, search.low[&node].min(search.low[&next]));
+
+        // Node separates next's subtree from the rest, so the edges above it on the stack form a block
+        if search.low[&next] >= search.discovered[&node] {
+          let mut block = vec![];
+          while let Some((a, b)) = search.stack.pop() {
+            block.push((a.min(b), a.max(b)));
+            if (a, b) == (node, next) {
+              break;
+            }
+          }
+          search.blocks.push(block);
+        }
+      } else if Some(next) != parent && search.discovered[&next] < search.discovered[&node] {
+        search.stack.push((node, next));
+        search.low.insert(node, search.low[&node].min(search.discovered[&next]));
+      }
+    }
+  }
+
+  let mut search = Search {
+    adjacency: adjacency(edges),
+    discovered: HashMap::new(),
+    low: HashMap::new(),
+    stack: vec![],
+    blocks: vec![],
+  };
+
+  let nodes: Vec<usize> = search.adjacency.keys().cloned().collect();
+  for node in nodes {
+    if !search.discovered.contains_key(&node) {
+      visit(&mut search, node, None);
+    }
+  }
+
+  search.blocks
+}
+
+/// A part of the block not drawn yet: a single edge between two drawn nodes, or a group of undrawn nodes with their
+/// edges. Contacts are the drawn nodes it touches
+struct Fragment {
+  contacts: BTreeSet<usize>,
+  /// Undrawn nodes, empty for a single edge
+  interior: BTreeSet<usize>,
+}
+
+/// Draw a biconnected block face by face (Demoucron, Malgrange and Pertuiset). Starting from a cycle, a path through a
+/// fragment is drawn into a face holding all of its contacts, splitting that face in two. A fragment with nowhere to
+/// go means the block isn't planar
+fn embed_block(edges: &[Edge]) -> Option<Vec<Face>> {
+  // A bridge has nothing to draw around
+  if edges.len() < 2 {
+    return Some(vec![]);
+  }
+
+  let adjacency = adjacency(edges);
+  // Euler's formula bounds the edges of a planar graph
+  if edges.len() > 3 * adjacency.len() - 6 {
+    return None;
+  }
+
+  let cycle = find_cycle(&adjacency);
+  let mut faces: Vec<Face> = vec![cycle.clone(), cycle.clone()];
+  let mut drawn_nodes: BTreeSet<usize> = cycle.iter().cloned().collect();
+  let mut drawn_edges: HashSet<Edge> = cycle
+    .iter()
+    .zip(cycle.iter().cycle().skip(1))
+    .map(|(a, b)| (*a.min(b), *a.max(b)))
+    .collect();
+
+  while drawn_edges.len() < edges.len() {
+    let fragments = fragments(edges, &adjacency, &drawn_nodes, &drawn_edges);
+
+    let mut placements: Vec<(&Fragment, Vec<usize>)> = vec![];
+    for fragment in fragments.iter() {
+      let admissible: Vec<usize> = (0..faces.len())
+        .filter(|f| fragment.contacts.iter().all(|contact| faces[*f].contains(contact)))
+        .collect();
+      if admissible.is_empty() {
+        return None;
+      }
+      placements.push((fragment, admissible));
+    }
+
+    // A fragment with only one possible face must go there, otherwise any choice works
+    let (fragment, admissible) = placements
+      .iter()
+      .find(|(_, admissible)| admissible.len() == 1)
+      .unwrap_or(&placements[0]);
+
+    let path = fragment_path(fragment, &adjacency);
+    let face = faces.swap_remove(admissible[0]);
+    let (face_a, face_b) = split_face(&face, &path);
+    faces.push(face_a);
+    faces.push(face_b);
+
+    drawn_nodes.extend(path.iter().cloned());
+    drawn_edges.extend(path.windows(2).map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1]))));
+  }
+
+  Some(faces)
+}
+
+/// Any cycle, found by following edges until one leads back to a node on the current path
+fn find_cycle(adjacency: &Adjacency) -> Vec<usize> {
+  let start = *adjacency.keys().next().unwrap();
+  let mut path: Vec<usize> = vec![start];
+  let mut visited: HashSet<usize> = HashSet::from([start]);
+
+  loop {
+    let node = *path.last().unwrap();
+    let previous = path.len().checked_sub(2).map(|i| path[i]);
+
+    if let Some(back) = adjacency[&node].iter().find(|next| Some(**next) != previous && path.contains(next)) {
+      let from = path.iter().position(|n| n == back).unwrap();
+      return path[from..].to_vec();
+    }
+
+    match adjacency[&node].iter().find(|next| !visited.contains(next)) {
+      Some(next) => {
+        visited.insert(*next);
+        path.push(*next);
+      }
+      // Dead end, which a biconnected block doesn't have, but step back anyway
+      None => {
+        path.pop();
+      }
+    }
+  }
+}
+
+fn fragments(edges: &[Edge], adjacency: &Adjacency, drawn_nodes: &BTreeSet<usize>, drawn_edges: &HashSet<Edge>) -> Vec<Fragment> {
+  let mut fragments: Vec<Fragment> = edges
+    .iter()
+    .filter(|edge| !drawn_edges.contains(edge) && drawn_nodes.contains(&edge.0) && drawn_nodes.contains(&edge.1))
+    .map(|(a, b)| Fragment {
+      contacts: BTreeSet::from([*a, *b]),
+      interior: BTreeSet::new(),
+    })
+    .collect();
+
+  let mut seen: HashSet<usize> = HashSet::new();
+  for start in adjacency.keys().filter(|node| !drawn_nodes.contains(node)) {
+    if !seen.insert(*start) {
+      continue;
+    }
+
+    let mut fragment = Fragment {
+      contacts: BTreeSet::new(),
+      interior: BTreeSet::from([*start]),
+    };
+    let mut queue = vec![*start];
+    while let Some(node) = queue.pop() {
+      for next in &adjacency[&node] {
+        if drawn_nodes.contains(next) {
+          fragment.contacts.insert(*next);
+        } else if seen.insert(*next) {
+          fragment.interior.insert(*next);
+          queue.push(*next);
+        }
+      }
+    }
+    fragments.push(fragment);
+  }
+
+  fragments
+}
+
+/// A path through the fragment between two of its contacts
+fn fragment_path(fragment: &Fragment, adjacency: &Adjacency) -> Vec<usize> {
+  let start = *fragment.contacts.iter().next().unwrap();
+  if fragment.interior.is_empty() {
+    return fragment.contacts.iter().cloned().collect();
+  }
+
+  let mut previous: HashMap<usize, usize> = HashMap::new();
+  let mut queue: VecDeque<usize> = VecDeque::new();
+  for next in adjacency[&start].iter().filter(|next| fragment.interior.contains(next)) {
+    previous.insert(*next, start);
+    queue.push_back(*next);
+  }
+
+  while let Some(node) = queue.pop_front() {
+    if let Some(end) = adjacency[&node]
+      .iter()
+      .find(|next| **next != start && fragment.contacts.contains(next))
+    {
+      let mut path = vec![*end, node];
+      while *path.last().unwrap() != start {
+        path.push(previous[path.last().unwrap()]);
+      }
+      path.reverse();
+      return path;
+    }
+
+    for next in adjacency[&node].iter().filter(|next| fragment.interior.contains(next)) {
+      if !previous.contains_key(next) {
+        previous.insert(*next, node);
+        queue.push_back(*next);
+      }
+    }
+  }
+
+  unreachable!("a fragment of a biconnected block has a path between two contacts")
+}
+
+/// Draw a path between two nodes of a face across it, giving the two faces on either side
+fn split_face(face: &Face, path: &[usize]) -> (Face, Face) {
+  let start = face.iter().position(|node| *node == path[0]).unwrap();
+  let end = face.iter().position(|node| node == path.last().unwrap()).unwrap();
+  let inner = &path[1..path.len() - 1];
+
+  // Around the face from one end of the path to the other, inclusive
+  let around = |from: usize, to: usize| -> Face {
+    let mut nodes = vec![];
+    let mut i = from;
+    loop {
+      nodes.push(face[i]);
+      if i == to {
+        return nodes;
+      }
+      i = (i + 1) % face.len();
+    }
+  };
+
+  let mut face_a = around(start, end);
+  face_a.extend(inner.iter().rev());
+  let mut face_b = around(end, start);
+  face_b.extend(inner.iter());
+
+  (face_a, face_b)
+}
+
+/// Tutte's barycentric embedding of a planar graph. The longest face is pinned around a unit circle, then every other
+/// node of its block is moved to the average of its neighbours until they settle. The rest of the graph, including
+/// graphs without a cycle, is fanned out from there by spread_trees
+fn tutte_positions(edges: &[Edge]) -> HashMap<usize, (f64, f64)> {
+  let mut positions: HashMap<usize, (f64, f64)> = HashMap::new();
+
+  let outer: Face = embed(edges)
+    .unwrap_or_default()
+    .into_iter()
+    .flatten()
+    .fold(vec![], |longest, face| if face.len() > longest.len() { face } else { longest });
+
+  for (i, node) in outer.iter().enumerate() {
+    let angle = 2.0 * PI * i as f64 / outer.len() as f64;
+    positions.insert(*node, (angle.cos(), angle.sin()));
+  }
+
+  // Only the outer face's own block is averaged, anything hanging off it would settle onto the node it hangs from
+  let block: Vec<Edge> = match outer.get(0..2) {
+    Some([a, b]) => blocks(edges)
+      .into_iter()
+      .find(|block| block.contains(&(*a.min(b), *a.max(b))))
+      .unwrap_or_default(),
+    _ => vec![],
+  };
+  let block_adjacency = adjacency(&block);
+  let free: Vec<usize> = block_adjacency
+    .keys()
+    .filter(|node| !positions.contains_key(node))
+    .cloned()
+    .collect();
+  for node in free.iter() {
+    positions.insert(*node, (0.0, 0.0));
+  }
+
+  for _ in 0..TUTTE_ITERATIONS {
+    let mut moved: f64 = 0.0;
+    for node in free.iter() {
+      let neighbours = &block_adjacency[node];
+      let (sum_x, sum_y) = neighbours
+        .iter()
+        .fold((0.0, 0.0), |(x, y), next| (x + positions[next].0, y + positions[next].1));
+      let average = (sum_x / neighbours.len() as f64, sum_y / neighbours.len() as f64);
+      let (x, y) = positions[node];
+      moved = moved.max((average.0 - x).abs() + (average.1 - y).abs());
+      positions.insert(*node, average);
+    }
+
+    if moved < 1e-9 {
+      break;
+    }
+  }
+
+  // A side of the pinned polygon, so the trees are drawn at the same scale as the block
+  let step = if outer.is_empty() {
+    1.0
+  } else {
+    2.0 * (PI / outer.len() as f64).sin()
+  };
+  spread_trees(&adjacency(edges), &mut positions, step);
+
+  positions
+}
+
+/// Position every node that has none on trees grown breadth first from the positioned nodes. Each tree fans out from
+/// its root, away from the middle of the drawing, one step further out per level, and each branch gets a share of the
+/// fan by how many leaves it has. A part of the graph with nothing positioned is rooted at its lowest node, to the
+/// right of everything drawn so far. Edges left out of the trees are ignored, so cycles hanging off the drawing are
+/// drawn as trees too
+fn spread_trees(adjacency: &Adjacency, positions: &mut HashMap<usize, (f64, f64)>, step: f64) {
+  let mut roots: Vec<usize> = adjacency.keys().filter(|node| positions.contains_key(node)).cloned().collect();
+
+  while let Some(first) = adjacency.keys().find(|node| !positions.contains_key(node)) {
+    if roots.is_empty() {
+      let first = *first;
+      let right = positions.values().map(|(x, _)| *x).reduce(f64::max);
+      let children = tree_children(adjacency, positions, &[first]);
+      let x = match right {
+        Some(right) => right + step * (tree_height(first, &children) + 1) as f64,
+        None => 0.0,
+      };
+      positions.insert(first, (x, 0.0));
+      roots.push(first);
+    }
+
+    let children = tree_children(adjacency, positions, &roots);
+    let count = roots.len() as f64;
+    let middle = roots.iter().fold((0.0, 0.0), |(x, y), root| {
+      (x + positions[root].0 / count, y + positions[root].1 / count)
+    });
+
+    for root in roots.iter() {
+      let (x, y) = positions[root];
+      let (dx, dy) = (x - middle.0, y - middle.1);
+      // A root in the middle has no outward direction, so its tree fans out all the way around
+      let fan = if dx.abs() + dy.abs() < 1e-9 {
+        (0.0, 2.0 * PI)
+      } else {
+        let angle = dy.atan2(dx);
+        (angle - PI / 4.0, angle + PI / 4.0)
+      };
+      fan_out(*root, (x, y), fan, 1, &children, positions, step);
+    }
+
+    roots.clear();
+  }
+}
+
+/// Children of each node on trees grown breadth first from the roots, over nodes without a position
+fn tree_children(adjacency: &Adjacency, positions: &HashMap<usize, (f64, f64)>, roots: &[usize]) -> HashMap<usize, Vec<usize>> {
+  let mut seen: HashSet<usize> = roots.iter().cloned().collect();
+  let mut queue: VecDeque<usize> = roots.iter().cloned().collect();
+  let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+
+  while let Some(node) = queue.pop_front() {
+    for next in adjacency[&node].iter() {
+      if !positions.contains_key(next) && seen.insert(*next) {
+        children.entry(node).or_default().push(*next);
+        queue.push_back(*next);
+      }
+    }
+  }
+
+  children
+}
+
+fn tree_height(node: usize, children: &HashMap<usize, Vec<usize>>) -> usize {
+  children
+    .get(&node)
+    .map(|kids| kids.iter().map(|kid| tree_height(*kid, children) + 1).max().unwrap_or(0))
+    .unwrap_or(0)
+}
+
+fn tree_leaves(node: usize, children: &HashMap<usize, Vec<usize>>) -> usize {
+  children
+    .get(&node)
+    .map(|kids| kids.iter().map(|kid| tree_leaves(*kid, children)).sum())
+    .unwrap_or(1)
+}
+
+/// Place the children of node within the fan of angles around the root, depth steps out from it
+fn fan_out(
+  node: usize,
+  root: (f64, f64),
+  (from, to): (f64, f64),
+  depth: usize,
+  children: &HashMap<usize, Vec<usize>>,
+  positions: &mut HashMap<usize, (f64, f64)>,
+  step: f64,
+) {
+  let kids = match children.get(&node) {
+    Some(kids) => kids,
+    None => return,
+  };
+  let leaves = tree_leaves(node, children) as f64;
+
+  let mut start = from;
+  for kid in kids {
+    let share = (to - from) * tree_leaves(*kid, children) as f64 / leaves;
+    let angle = start + share / 2.0;
+    let distance = step * depth as f64;
+    positions.insert(*kid, (root.0 + distance * angle.cos(), root.1 + distance * angle.sin()));
+    fan_out(*kid, root, (start, start + share), depth + 1, children, positions, step);
+    start += share;
+  }
+}
@@ -0,0 +1,236 @@
+use super::{create_neighbour_map, GraphResult, MapGraph};
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use std::collections::{HashSet, VecDeque};
+
+type Graph = StableGraph<usize, usize>;
+
+/// Clean-up passes run on a generated graph before rooms are placed
+#[derive(Clone)]
+pub struct PostprocessConfig {
+  /// Remove every node outside the largest weakly connected component
+  pub keep_largest_component: bool,
+  /// Join every smaller component to the largest with one edge. With neither component pass, nodes cut off from the
+  /// rest of the graph are left (the crawler skips them as detached)
+  pub connect_components: bool,
+  /// Remove or move edges so no node has more neighbours than its room is sure to have doors for
+  pub cap_degree: bool,
+  /// Add the reverse of every edge
+  pub bidirectional: bool,
+}
+
+impl Default for PostprocessConfig {
+  fn default() -> PostprocessConfig {
+    PostprocessConfig {
+      keep_largest_component: false,
+      connect_components: true,
+      cap_degree: true,
+      bidirectional: false,
+    }
+  }
+}
+
+/// Run the configured passes over a map graph, then rebuild its neighbour map and drop the roles of removed nodes.
+/// Self-loops and duplicate edges are always removed
+pub fn apply(map_graph: &mut MapGraph, config: &PostprocessConfig, max_degree: usize) {
+  let mut result: GraphResult = (map_graph.graph.clone(), map_graph.nodes.clone());
+
+  remove_self_loops_and_duplicates(&mut result);
+
+  // Keeping the largest component first leaves nothing to connect
+  if config.keep_largest_component {
+    keep_largest_component(&mut result);
+  }
+
+  if config.connect_components {
+    connect_components(&mut result);
+  }
+
+  if config.cap_degree {
+    cap_degree(&mut result, max_degree);
+  }
+
+  if config.bidirectional {
+    make_bidirectional(&mut result);
+  }
+
+  let (graph, nodes) = result;
+  map_graph.roles.retain(|node_idx, _| graph.contains_node(NodeIndex::new(*node_idx)));
+  map_graph.neighbour_map = create_neighbour_map((graph.clone(), nodes.clone()));
+  map_graph.graph = graph;
+  map_graph.nodes = nodes;
+}
+
+/// Remove edges from a node to itself, and repeats of an edge in the same direction
+pub fn remove_self_loops_and_duplicates((graph, _): &mut GraphResult) {
+  let mut seen: HashSet<(NodeIndex, NodeIndex)> = HashSet::new();
+  let edges: Vec<_> = graph.edge_indices().collect();
+
+  for edge in edges {
+    let (a, b) = graph.edge_endpoints(edge).unwrap();
+    if a == b || !seen.insert((a, b)) {
+      graph.remove_edge(edge);
+    }
+  }
+}
+
+/// Groups of nodes joined by edges in either direction, in the order of their first node
+pub fn components((graph, nodes): &GraphResult) -> Vec<Vec<NodeIndex>> {
+  let mut seen: HashSet<NodeIndex> = HashSet::new();
+  let mut components = vec![];
+
+  for node in nodes {
+    if !seen.insert(*node) {
+      continue;
+    }
+
+    let mut component = vec![*node];
+    let mut queue = vec![*node];
+    while let Some(current) = queue.pop() {
+      for next in graph.neighbors_undirected(current) {
+        if seen.insert(next) {
+          component.push(next);
+          queue.push(next);
+        }
+      }
+    }
+    components.push(component);
+  }
+
+  components
+}
+
+/// Index of the component with the most nodes, the earliest on a tie
+fn largest(components: &[Vec<NodeIndex>]) -> usize {
+  (0..components.len()).rev().max_by_key(|i| components[*i].len()).unwrap_or(0)
+}
+
+/// Remove every node outside the largest weakly connected component
+pub fn keep_largest_component(result: &mut GraphResult) {
+  let mut components = components(result);
+  if components.len() < 2 {
+    return;
+  }
+
+  let keep: HashSet<NodeIndex> = components.swap_remove(largest(&components)).into_iter().collect();
+  let (graph, nodes) = result;
+  for node in components.into_iter().flatten() {
+    graph.remove_node(node);
+  }
+  nodes.retain(|node| keep.contains(node));
+}
+
+/// Join every smaller component to the largest, each with one edge between the two nodes with the fewest neighbours
+pub fn connect_components(result: &mut GraphResult) {
+  let mut components = components(result);
+  if components.len() < 2 {
+    return;
+  }
+
+  let mut main = components.remove(largest(&components));
+  let (graph, _) = result;
+
+  for component in components {
+    let from = *main.iter().min_by_key(|node| degree(graph, **node)).unwrap();
+    let to = *component.iter().min_by_key(|node| degree(graph, **node)).unwrap();
+    graph.add_edge(from, to, 0);
+    main.extend(component);
+  }
+}
+
+/// Bring every node down to at most max_degree neighbours. An edge on a loop is removed outright, otherwise the
+/// neighbour is moved onto the nearest node with a spare door, so the graph stays connected. Nodes that can't be
+/// brought down either way are left over the limit
+pub fn cap_degree((graph, nodes): &mut GraphResult, max_degree: usize) {
+  for node in nodes.iter() {
+    while degree(graph, *node) > max_degree {
+      let mut neighbours = neighbours(graph, *node);
+      // Busiest neighbours give up the edge first
+      neighbours.sort_by_key(|neighbour| (std::cmp::Reverse(degree(graph, *neighbour)), neighbour.index()));
+
+      if let Some(neighbour) = neighbours.iter().find(|neighbour| has_other_path(graph, *node, **neighbour)) {
+        remove_edges_between(graph, *node, *neighbour);
+        continue;
+      }
+
+      // The least busy neighbour is the cheapest to move
+      let neighbour = *neighbours.last().unwrap();
+      match nearest_with_spare_door(graph, *node, neighbour, max_degree) {
+        Some(target) => {
+          remove_edges_between(graph, *node, neighbour);
+          graph.add_edge(target, neighbour, 0);
+        }
+        None => break,
+      }
+    }
+  }
+}
+
+/// Add the reverse of every edge that doesn't already have one
+pub fn make_bidirectional((graph, _): &mut GraphResult) {
+  let edges: Vec<_> = graph.edge_indices().filter_map(|edge| graph.edge_endpoints(edge)).collect();
+
+  for (a, b) in edges {
+    if graph.find_edge(b, a).is_none() {
+      graph.add_edge(b, a, 0);
+    }
+  }
+}
+
+/// Distinct neighbours, incoming and outgoing
+fn neighbours(graph: &Graph, node: NodeIndex) -> Vec<NodeIndex> {
+  let mut neighbours: Vec<NodeIndex> = graph.neighbors_undirected(node).filter(|next| *next != node).collect();
+  neighbours.sort();
+  neighbours.dedup();
+  neighbours
+}
+
+fn degree(graph: &Graph, node: NodeIndex) -> usize {
+  neighbours(graph, node).len()
+}
+
+fn remove_edges_between(graph: &mut Graph, a: NodeIndex, b: NodeIndex) {
+  while let Some((edge, _)) = graph.find_edge_undirected(a, b) {
+    graph.remove_edge(edge);
+  }
+}
+
+/// Whether b can still be reached from a without the edges directly between them
+fn has_other_path(graph: &Graph, a: NodeIndex, b: NodeIndex) -> bool {
+  let mut seen: HashSet<NodeIndex> = HashSet::from([a]);
+  let mut queue: Vec<NodeIndex> = neighbours(graph, a).into_iter().filter(|next| *next != b).collect();
+  seen.extend(queue.iter().cloned());
+
+  while let Some(current) = queue.pop() {
+    for next in neighbours(graph, current) {
+      if next == b {
+        return true;
+      }
+      if seen.insert(next) {
+        queue.push(next);
+      }
+    }
+  }
+
+  false
+}
+
+/// Breadth first search from a node, not passing through excluded, for the closest other node with fewer than
+/// max_degree neighbours
+fn nearest_with_spare_door(graph: &Graph, node: NodeIndex, excluded: NodeIndex, max_degree: usize) -> Option<NodeIndex> {
+  let mut seen: HashSet<NodeIndex> = HashSet::from([node, excluded]);
+  let mut queue: VecDeque<NodeIndex> = VecDeque::from([node]);
+
+  while let Some(current) = queue.pop_front() {
+    for next in neighbours(graph, current) {
+      if !seen.insert(next) {
+        continue;
+      }
+      if degree(graph, next) < max_degree {
+        return Some(next);
+      }
+      queue.push_back(next);
+    }
+  }
+
+  None
+}
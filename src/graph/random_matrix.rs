@@ -6,8 +6,7 @@ use rand::{prelude::SliceRandom, Rng};
 
 const MAX_CONNECTIONS: usize = 2;
 
-pub fn new() -> GraphResult {
-  let mut rng = rand::thread_rng();
+pub fn new<R: Rng>(rng: &mut R) -> GraphResult {
   let mut graph = StableGraph::<usize, usize>::new();
 
   let mut all_nodes: Vec<NodeIndex<u32>> = Vec::new();
@@ -27,7 +26,7 @@ pub fn new() -> GraphResult {
 
   for i in 0..node_count {
     // randomly select two viable nodes
-    let random_nodes = get_random_nodes(&graph, &connected_nodes, &all_nodes);
+    let random_nodes = get_random_nodes(&graph, &connected_nodes, &all_nodes, rng);
 
     if let Some((node_1, node_2)) = random_nodes {
       if !connected_nodes.contains(&node_2) {
@@ -61,20 +60,20 @@ fn can_make_edge(
 }
 
 /// Randomly attempts to make a connection with all currently connected nodes
-fn get_random_nodes(
+fn get_random_nodes<R: Rng>(
   graph: &StableGraph<usize, usize>,
-  connected: &Vec<NodeIndex<u32>>,
-  all_nodes: &Vec<NodeIndex<u32>>,
+  connected: &[NodeIndex<u32>],
+  all_nodes: &[NodeIndex<u32>],
+  rng: &mut R,
 ) -> Option<(NodeIndex<u32>, NodeIndex<u32>)> {
-  let mut rng = rand::thread_rng();
-  let mut remaining_nodes = connected.clone();
+  let mut remaining_nodes = connected.to_vec();
   let edges: Vec<(NodeIndex, NodeIndex)> = graph.edge_references().map(|e| (e.source(), e.target())).collect();
 
-  while remaining_nodes.len() != 0 {
-    let node_1 = remaining_nodes.choose(&mut rng).unwrap();
-    let node_2 = all_nodes.choose(&mut rng).unwrap();
+  while !remaining_nodes.is_empty() {
+    let node_1 = remaining_nodes.choose(rng).unwrap();
+    let node_2 = all_nodes.choose(rng).unwrap();
 
-    if can_make_edge(&graph, edges.clone(), *node_1, *node_2) {
+    if can_make_edge(graph, edges.clone(), *node_1, *node_2) {
       return Some((*node_1, *node_2));
     } else {
       // remove node from remaining_nodes
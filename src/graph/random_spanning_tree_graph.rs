@@ -0,0 +1,56 @@
+use super::GraphResult;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::unionfind::UnionFind;
+use petgraph::visit::NodeIndexable;
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashSet;
+
+/// Return a random spanning tree of another generator's graph, with
+/// ``extra_edges`` of the left over edges added back to close loops.
+///
+/// The tree is built with Kruskal's algorithm over the edges in a random
+/// order, ignoring edge direction. Self-loops and parallel edges of the input
+/// are dropped. With ``extra_edges`` set to ``0`` the result is a tree (or a
+/// forest when the input is disconnected); raising it trades dead ends for
+/// loops until every edge of the input is back. Nodes and their weights are
+/// kept as they are.
+///
+/// :param GraphResult source: The graph to take the spanning tree of
+/// :param int extra_edges: How many non tree edges to add back
+/// :param rng: The random number generator to draw from
+///
+/// :returns: The spanning tree with loops
+/// :rtype: PyDiGraph
+///
+pub fn new<R: Rng>((source, nodes): GraphResult, extra_edges: usize, rng: &mut R) -> GraphResult {
+  let mut edges: Vec<(NodeIndex, NodeIndex)> = source
+    .edge_indices()
+    .filter_map(|edge| source.edge_endpoints(edge))
+    .filter(|(a, b)| a != b)
+    .collect();
+
+  // Drop parallel edges in either direction, keeping the first
+  let mut seen: HashSet<(usize, usize)> = HashSet::new();
+  edges.retain(|(a, b)| seen.insert((a.index().min(b.index()), a.index().max(b.index()))));
+  edges.shuffle(rng);
+
+  let mut graph = source;
+  graph.clear_edges();
+
+  let mut components = UnionFind::<usize>::new(graph.node_bound());
+  let mut loops: Vec<(NodeIndex, NodeIndex)> = vec![];
+
+  for (a, b) in edges {
+    if components.union(a.index(), b.index()) {
+      graph.add_edge(a, b, 0);
+    } else {
+      loops.push((a, b));
+    }
+  }
+
+  for (a, b) in loops.into_iter().take(extra_edges) {
+    graph.add_edge(a, b, 0);
+  }
+
+  (graph, nodes)
+}
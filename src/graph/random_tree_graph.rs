@@ -0,0 +1,65 @@
+use super::GraphResult;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use rand::Rng;
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+/// Return a random directed tree, grown breadth first from node 0 with edges
+/// pointing from parent to child.
+///
+/// Each node pulled off the frontier either becomes a dead end with
+/// probability ``dead_end_ratio`` or gets a number of children drawn uniformly
+/// from ``branching``. The root never becomes a dead end, and neither does the
+/// last node on the frontier while nodes remain, so the tree only stops short
+/// of ``num_nodes`` when ``max_depth`` runs out.
+///
+/// :param int num_nodes: The most nodes to create in the tree
+/// :param int max_depth: Nodes at this depth are always leaves
+/// :param range branching: How many children a non dead end node gets
+/// :param float dead_end_ratio: The probability of a node having no children
+/// :param rng: The random number generator to draw from
+///
+/// :returns: The generated tree
+/// :rtype: PyDiGraph
+///
+pub fn new<R: Rng>(num_nodes: usize, max_depth: usize, branching: RangeInclusive<usize>, dead_end_ratio: f64, rng: &mut R) -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let mut nodes: Vec<NodeIndex> = vec![];
+
+  if num_nodes == 0 {
+    return (graph, nodes);
+  }
+
+  nodes.push(graph.add_node(0));
+  let mut frontier: VecDeque<(NodeIndex, usize)> = VecDeque::from([(nodes[0], 0)]);
+
+  while let Some((parent, depth)) = frontier.pop_front() {
+    if nodes.len() >= num_nodes {
+      break;
+    }
+
+    if depth >= max_depth {
+      continue;
+    }
+
+    let is_root = parent == nodes[0];
+    if !is_root && !frontier.is_empty() && rng.gen_bool(dead_end_ratio.clamp(0.0, 1.0)) {
+      continue;
+    }
+
+    let children = rng.gen_range(branching.clone()).max(1).min(num_nodes - nodes.len());
+    for _ in 0..children {
+      let child = graph.add_node(0);
+      graph.add_edge(parent, child, 0);
+      nodes.push(child);
+      frontier.push_back((child, depth + 1));
+    }
+  }
+
+  // Reset node weights to be sequentially heavier
+  for (i, nw) in graph.node_weights_mut().enumerate() {
+    *nw = i + 1;
+  }
+
+  (graph, nodes)
+}
@@ -1,44 +1,199 @@
 mod common;
 mod crawler;
 mod debug;
+mod decoration;
 mod graph;
 mod map;
-use graph::MapGraph;
-use map::room_templates;
+mod minimap;
+mod spawner;
+mod traversal;
+
+#[cfg(test)]
+mod tests;
+use graph::{critical_path::SavePointConfig, layout::LayoutConfig, postprocess::PostprocessConfig, MapGraph};
+use map::{room::RoomType, room_templates::RoomTemplates};
+use minimap::{Minimap, MinimapConfig};
+use petgraph::stable_graph::NodeIndex;
+use rand::{rngs::StdRng, SeedableRng};
+use spawner::SpawnTable;
+use std::path::PathBuf;
 use std::time::Instant;
+use traversal::JumpPhysics;
+
+/// Crawls that leave out the boss room are thrown away and tried again, up to this many in total
+const MAX_CRAWLS: usize = 8;
 
 // #[derive(Default)]
 pub struct Config {
   width: i32,
   height: i32,
+  /// Where precalculated template combinations are stored, None always recalculates them
+  combination_cache: Option<PathBuf>,
+  /// Clean-up passes over the generated graph (connectivity, degree cap), None uses the graph as generated
+  postprocess: Option<PostprocessConfig>,
+  /// Preferred node positions that rooms are placed towards, None places rooms on random sides
+  layout: Option<LayoutConfig>,
+  /// Rejects room combinations the player couldn't travel through and adds platforms to rooms that need them,
+  /// None skips both
+  physics: Option<JumpPhysics>,
+  /// Save points, shops and teleports along the path to the boss, None leaves them out
+  save_points: Option<SavePointConfig>,
+  /// How many secret rooms to hide behind breakable walls, None leaves them out
+  secret_rooms: Option<usize>,
+  /// Enemies and items placed in rooms, None leaves rooms empty
+  spawn_table: Option<SpawnTable>,
+  /// Seeds every random choice, the same seed and config always give the same map
+  seed: u64,
+  /// Where the map's layers are exported for the game engine, None skips exporting
+  export_path: Option<PathBuf>,
+  /// Coarse world map of the placed rooms, None skips it
+  minimap: Option<MinimapConfig>,
 }
 
 fn main() {
-  let config = Config { width: 100, height: 100 };
+  let config = Config {
+    width: 100,
+    height: 100,
+    combination_cache: Some(PathBuf::from("target/room_combinations.json")),
+    postprocess: Some(PostprocessConfig::default()),
+    layout: Some(LayoutConfig::default()),
+    physics: Some(JumpPhysics::default()),
+    save_points: Some(SavePointConfig::default()),
+    secret_rooms: Some(2),
+    spawn_table: Some(SpawnTable::default()),
+    // cargo run --release -- <seed> regenerates a map
+    seed: std::env::args()
+      .nth(1)
+      .and_then(|arg| arg.parse().ok())
+      .unwrap_or_else(rand::random),
+    export_path: Some(PathBuf::from("target/map.json")),
+    minimap: Some(MinimapConfig::default()),
+  };
+
+  println!("seed: {}", config.seed);
 
   let time_benchmark = Instant::now();
-  let map_graph = graph::random_graph();
-  let map = generate_map(&map_graph, config);
+  let mut templates = load_templates(&config);
+  let mut map_graph = generate_graph(&config, &templates);
+
+  let export_path = config.export_path.clone();
+  let minimap_config = config.minimap.clone();
+  let map = generate_map(&mut map_graph, &mut templates, config);
+  let report = map::validation::validate(&map, &map_graph);
+  if !report.is_valid() {
+    println!("validation: {}", report);
+  }
+
+  if let Some(path) = export_path {
+    if let Err(error) = map::export::save(&map, &path) {
+      println!("Unable to export map: {}", error);
+    }
+  }
+
+  if let Some(minimap_config) = minimap_config {
+    let mut minimap = Minimap::new(&map, minimap_config.cell_size);
+    // The player starts out in the start room
+    if let Some(start) = map_graph.nodes.first() {
+      minimap.visit_room(start.index());
+    }
+    print!("{}", minimap.to_ascii());
+
+    if let Some(path) = minimap_config.json_path {
+      if let Err(error) = minimap.save_json(&path) {
+        println!("Unable to export minimap: {}", error);
+      }
+    }
+    if let Some(path) = minimap_config.png_path {
+      if let Err(error) = minimap.save_png(&path, minimap_config.png_scale) {
+        println!("Unable to render minimap: {}", error);
+      }
+    }
+  }
   // ---------- debug ------------
   // debug::print_er_diagram(&map_graph.graph, &map_graph.nodes);
   // debug::print_plantuml_map(&map_graph.graph, &map);
   // debug::print_plantuml_nodes(&map_graph.graph, &map_graph.nodes);
-  debug::print_map(map.tiles, map.width);
+  debug::print_map(map.tiles.clone(), map.width);
   // debug::print_map_history(map.history, map.width);
 
   // ---------- benchmark ------------
   let elapsed = time_benchmark.elapsed();
-  println!("{}/{} rooms generated in: {:.2?}", map.rooms.len(), map_graph.nodes.len(), elapsed);
+  // Secret rooms aren't graph nodes
+  let graph_rooms = map
+    .rooms
+    .keys()
+    .filter(|idx| map_graph.graph.contains_node(NodeIndex::new(**idx)))
+    .count();
+  println!("{}/{} rooms generated in: {:.2?}", graph_rooms, map_graph.nodes.len(), elapsed);
 }
 
-pub fn generate_map(map_graph: &MapGraph, config: Config) -> map::Map {
+/// Room templates with their precalculated combinations, read from the cache when there is one
+pub fn load_templates(config: &Config) -> RoomTemplates {
+  match &config.combination_cache {
+    Some(path) => RoomTemplates::load_or_calculate(path),
+    None => RoomTemplates::new(),
+  }
+}
+
+pub fn generate_graph(config: &Config, templates: &RoomTemplates) -> MapGraph {
+  let mut map_graph = graph::random_graph(&mut StdRng::seed_from_u64(config.seed));
+
+  if let Some(postprocess) = &config.postprocess {
+    // Every node's room has to fit its neighbours, including the start and boss rooms with fewer templates to pick from
+    let max_degree = templates.guaranteed_door_capacity() as usize;
+    graph::postprocess::apply(&mut map_graph, postprocess, max_degree);
+  }
+
+  if let Some(layout) = &config.layout {
+    graph::layout::apply(&mut map_graph, layout);
+  }
+
+  if let Some(save_points) = &config.save_points {
+    graph::critical_path::place_save_points(&mut map_graph, save_points);
+  }
+
+  map_graph
+}
+
+pub fn generate_map(map_graph: &mut MapGraph, templates: &mut RoomTemplates, config: Config) -> map::Map {
+  let mut rng = StdRng::seed_from_u64(config.seed.rotate_left(16));
+
+  let mut map = crawl(map_graph, templates, &config, &mut rng);
+  for _ in 1..MAX_CRAWLS {
+    if map.rooms.values().any(|room| room.template.room_type == RoomType::Boss) {
+      break;
+    }
+    println!("Boss room not placed, crawling again..");
+    map = crawl(map_graph, templates, &config, &mut rng);
+  }
+
+  if let Some(count) = config.secret_rooms {
+    crawler::secret_rooms::place(map_graph, &mut map, templates, count, &config, &mut rng);
+  }
+
+  if let Some(physics) = &config.physics {
+    for node_idx in decoration::platforms::decorate(&mut map, physics, config.seed) {
+      println!("Doors out of reach in room {}", node_idx);
+    }
+    decoration::interior::fill(&mut map, physics, config.seed);
+    traversal::annotate_rooms(&mut map, physics);
+  }
+
+  if let Some(spawn_table) = &config.spawn_table {
+    spawner::spawn(&mut map, map_graph, spawn_table, config.seed);
+  }
+
+  map
+}
+
+/// Place a room for each node, crawling the graph outwards from every node in turn
+fn crawl(map_graph: &MapGraph, templates: &mut RoomTemplates, config: &Config, rng: &mut StdRng) -> map::Map {
   let mut map = map::Map::new(config.width, config.height);
-  let mut rng = rand::thread_rng();
-  let mut templates = room_templates::RoomTemplates::new();
 
   for node in map_graph.nodes.iter() {
-    let mut chain = Vec::from([node.clone()]);
-    crawler::try_node_recursive(node, &map_graph, &mut map, &mut templates, &mut chain, &mut rng);
+    let mut chain = Vec::from([*node]);
+    crawler::try_node_recursive(node, map_graph, &mut map, templates, &mut chain, config, rng);
   }
+
   map
 }
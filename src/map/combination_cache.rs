@@ -0,0 +1,69 @@
+use super::room_templates::{CombinationTable, RoomTemplateMap};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Bump this whenever the combination algorithm changes, so old caches are recalculated
+pub const CACHE_VERSION: u64 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct CombinationCache {
+  /// CACHE_VERSION when the combinations were calculated
+  version: u64,
+  /// Hash of the template set these combinations were calculated for
+  hash: u64,
+  combinations: CombinationTable,
+}
+
+/// FNV-1a hash of every template's index, dimensions and tiles. The std hasher is not guaranteed to be stable between
+/// Rust releases, so it can't be used to key a file on disk
+pub fn template_hash(templates: &RoomTemplateMap) -> u64 {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  let mut write = |bytes: &[u8]| {
+    for byte in bytes {
+      hash ^= *byte as u64;
+      hash = hash.wrapping_mul(0x100000001b3);
+    }
+  };
+
+  let mut template_idxs: Vec<&usize> = templates.keys().collect();
+  template_idxs.sort();
+
+  for idx in template_idxs {
+    let template = templates.get(idx).unwrap();
+    write(&(*idx as u64).to_le_bytes());
+    write(&template.w.to_le_bytes());
+    write(&template.h.to_le_bytes());
+    write(&template.tiles);
+  }
+
+  hash
+}
+
+/// Load the cached combinations, only if they were calculated for the same template set by this CACHE_VERSION. A
+/// missing or unreadable file is None too
+pub fn load(path: &Path, hash: u64) -> Option<CombinationTable> {
+  let contents = fs::read_to_string(path).ok()?;
+  let cache: CombinationCache = serde_json::from_str(&contents).ok()?;
+
+  if cache.version == CACHE_VERSION && cache.hash == hash {
+    Some(cache.combinations)
+  } else {
+    None
+  }
+}
+
+pub fn save(path: &Path, hash: u64, combinations: &CombinationTable) -> io::Result<()> {
+  let cache = CombinationCache {
+    version: CACHE_VERSION,
+    hash,
+    combinations: combinations.clone(),
+  };
+
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+
+  fs::write(path, serde_json::to_string(&cache)?)
+}
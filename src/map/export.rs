@@ -0,0 +1,70 @@
+use super::{layers::Layers, Map, Spawn};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Serialize)]
+struct ExportedMap<'a> {
+  width: i32,
+  height: i32,
+  layers: &'a Layers,
+  rooms: Vec<ExportedRoom>,
+  spawns: &'a Vec<Spawn>,
+}
+
+#[derive(Serialize)]
+struct ExportedRoom {
+  node: usize,
+  template: usize,
+  x: i32,
+  y: i32,
+  w: i32,
+  h: i32,
+  tags: Vec<String>,
+  /// Map tile coordinates of the room's secret doors, which the collision layer shows as solid wall
+  secret_doors: Vec<(i32, i32)>,
+}
+
+/// Serialise the map's layers and rooms for a game engine to load
+pub fn to_json(map: &Map) -> serde_json::Result<String> {
+  let mut rooms: Vec<ExportedRoom> = map
+    .rooms
+    .iter()
+    .map(|(node, room)| {
+      let bounds = room.bounds();
+      ExportedRoom {
+        node: *node,
+        template: room.template.id,
+        x: bounds.x,
+        y: bounds.y,
+        w: bounds.w,
+        h: bounds.h,
+        tags: room.template.tags.clone(),
+        secret_doors: room
+          .doors_world()
+          .iter()
+          .filter(|door| door.secret)
+          .map(|door| (door.x, door.y))
+          .collect(),
+      }
+    })
+    .collect();
+  rooms.sort_by_key(|room| room.node);
+
+  serde_json::to_string(&ExportedMap {
+    width: map.width,
+    height: map.height,
+    layers: &map.layers,
+    rooms,
+    spawns: &map.spawns,
+  })
+}
+
+pub fn save(map: &Map, path: &Path) -> io::Result<()> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+
+  fs::write(path, to_json(map)?)
+}
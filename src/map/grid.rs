@@ -0,0 +1,92 @@
+use super::room::Room;
+
+/// A rectangle of tiles stored row by row, either owned (eg: a scratch canvas) or borrowed from a map
+pub struct Grid<T> {
+  tiles: T,
+  width: i32,
+  height: i32,
+}
+
+/// Tiles placed at a position on a grid. Coordinates are signed, so a stamp can hang off any edge of the grid
+#[derive(Clone, Copy)]
+pub struct Stamp<'a> {
+  pub x: i32,
+  pub y: i32,
+  pub w: i32,
+  pub tiles: &'a [u8],
+}
+
+impl<'a> Stamp<'a> {
+  pub fn new(x: i32, y: i32, w: i32, tiles: &'a [u8]) -> Stamp<'a> {
+    Stamp { x, y, w, tiles }
+  }
+
+  /// Every tile of the stamp, empty (0) or not, with its grid coordinates
+  pub fn cells(&self) -> impl Iterator<Item = (i32, i32, u8)> + 'a {
+    let Stamp { x, y, w, tiles } = *self;
+    tiles
+      .iter()
+      .enumerate()
+      .map(move |(idx, tile)| (x + idx as i32 % w, y + idx as i32 / w, *tile))
+  }
+}
+
+impl<'a> From<&'a Room> for Stamp<'a> {
+  fn from(room: &'a Room) -> Stamp<'a> {
+    Stamp::new(room.x, room.y, room.template.w, &room.tiles)
+  }
+}
+
+impl<T: AsRef<[u8]>> Grid<T> {
+  pub fn new(tiles: T, width: i32) -> Grid<T> {
+    let height = tiles.as_ref().len() as i32 / width;
+    Grid { tiles, width, height }
+  }
+
+  pub fn tiles(&self) -> &[u8] {
+    self.tiles.as_ref()
+  }
+
+  /// Index into the tiles, None off the grid
+  pub fn index(&self, x: i32, y: i32) -> Option<usize> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
+    }
+    Some((y * self.width + x) as usize)
+  }
+
+  pub fn get(&self, x: i32, y: i32) -> Option<u8> {
+    self.index(x, y).map(|idx| self.tiles()[idx])
+  }
+
+  /// Whether the stamp's whole rectangle, empty tiles included, lands on the grid
+  pub fn contains(&self, stamp: &Stamp) -> bool {
+    stamp.cells().all(|(x, y, _)| self.index(x, y).is_some())
+  }
+
+  /// Index of the first grid tile that one of the stamp's non-empty tiles lands on and `is_blocked` rejects
+  pub fn overlap(&self, stamp: &Stamp, is_blocked: impl Fn(u8) -> bool) -> Option<usize> {
+    stamp
+      .cells()
+      .filter(|(_, _, tile)| *tile != 0)
+      .filter_map(|(x, y, _)| self.index(x, y))
+      .find(|idx| is_blocked(self.tiles()[*idx]))
+  }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> Grid<T> {
+  /// Write the stamp's non-empty tiles onto the grid, skipping any that fall off it. `merge` is given the index, the
+  /// grid's tile and the stamp's tile, and returns the tile to write
+  pub fn blit(&mut self, stamp: &Stamp, mut merge: impl FnMut(usize, u8, u8) -> u8) {
+    for (x, y, tile) in stamp.cells() {
+      if tile == 0 {
+        continue;
+      }
+
+      if let Some(idx) = self.index(x, y) {
+        let tiles = self.tiles.as_mut();
+        tiles[idx] = merge(idx, tiles[idx], tile);
+      }
+    }
+  }
+}
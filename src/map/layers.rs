@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+// Collision layer values
+pub const EMPTY: u8 = 0;
+pub const SOLID: u8 = 1;
+pub const ONE_WAY_PLATFORM: u8 = 2;
+pub const LADDER: u8 = 3;
+pub const HAZARD: u8 = 4;
+
+/// Engine facing layers of the map, each the same size as Map::tiles. Map::tiles stays the generator's working
+/// structure, with possible doors and clash markers that mean nothing to the game
+#[derive(Clone, Serialize)]
+pub struct Layers {
+  /// What the player collides with: empty, solid, one way platform, ladder or hazard
+  pub collision: Vec<u8>,
+  /// 1 behind every open room tile, where the room's backdrop is drawn
+  pub background: Vec<u8>,
+  /// Foreground terrain, hazards and decor inside rooms
+  pub decoration: Vec<u8>,
+  /// Spawn markers for enemies and items
+  pub entities: Vec<u8>,
+  /// Node index of the room covering each tile, plus one. 0 where there is no room
+  pub room_id: Vec<u32>,
+}
+
+impl Layers {
+  pub fn new(tilecount: usize) -> Layers {
+    Layers {
+      collision: vec![EMPTY; tilecount],
+      background: vec![0; tilecount],
+      decoration: vec![0; tilecount],
+      entities: vec![0; tilecount],
+      room_id: vec![0; tilecount],
+    }
+  }
+
+  /// Write the structural layers for a room tile placed on the map
+  pub fn set_structure(&mut self, idx: usize, tile: u8, node_idx: usize) {
+    self.collision[idx] = collision(tile);
+    self.background[idx] = if collision(tile) == SOLID { 0 } else { 1 };
+    self.room_id[idx] = node_idx as u32 + 1;
+  }
+}
+
+/// Collision value of a room tile
+pub fn collision(tile: u8) -> u8 {
+  match tile {
+    // 7 - used door, 8 - interior space
+    7 | 8 => EMPTY,
+    11 => LADDER,
+    12 => ONE_WAY_PLATFORM,
+    // walls, unused and secret doors, clashes and platforms
+    _ => SOLID,
+  }
+}
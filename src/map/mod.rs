@@ -1,18 +1,31 @@
 use super::{
   common::{xy_idx, XY},
   debug, graph,
+  spawner::Spawn,
 };
+pub mod combination_cache;
+pub mod export;
+pub mod grid;
+pub mod layers;
 pub mod room;
+pub mod room_shapes;
+use grid::{Grid, Stamp};
+use layers::Layers;
 use room::{DoorsXY, Room, RoomType};
 pub mod room_template_definitions;
 use room_templates::RoomTemplates;
 pub mod room_templates;
+pub mod validation;
 use std::collections::HashMap;
 
 const DEBUG: bool = false;
 
 pub struct Map {
   pub tiles: Vec<u8>,
+  /// Collision, background, decoration, entity and room id layers for exporting
+  pub layers: Layers,
+  /// Enemy and item spawn points
+  pub spawns: Vec<Spawn>,
   pub history: Vec<Vec<u8>>,
   pub rooms: HashMap<usize, Room>,
   pub width: i32,
@@ -28,35 +41,35 @@ impl Map {
       height,
       rooms: HashMap::from([]),
       tiles: vec![0; tilecount],
+      layers: Layers::new(tilecount),
+      spawns: vec![],
       history: vec![],
     }
   }
-}
 
-pub fn add_or_update_room(map: &mut Map, node_idx: usize, room: Room) {
-  // start at x,y in the map
-  let is_new_room = !map.rooms.contains_key(&node_idx);
-  let mut idx = xy_idx(room.x, room.y, map.width);
-  let mut x = 1;
-
-  for tile in room.tiles.iter() {
-    let in_range = idx <= map.tiles.len();
-    // if this tile overlaps
-    if is_new_room && *tile != 0 && in_range && map.tiles[idx] == 8 {
-      map.tiles[idx] = 9; // 9 = clash
-    } else if *tile != 0 && in_range {
-      map.tiles[idx] = *tile;
+  /// Node index of the room covering a tile, for minimap reveal, camera bounds and spawning
+  pub fn room_at(&self, x: i32, y: i32) -> Option<usize> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
     }
 
-    // end of the row, move down one and back to the left
-    if x == room.template.w {
-      x = 1;
-      idx += (map.width - room.template.w + 1) as usize;
-    } else {
-      idx += 1;
-      x += 1;
+    match self.layers.room_id[xy_idx(x, y, self.width)] {
+      0 => None,
+      id => Some(id as usize - 1),
     }
   }
+}
+
+pub fn add_or_update_room(map: &mut Map, node_idx: usize, room: Room) {
+  let is_new_room = !map.rooms.contains_key(&node_idx);
+  let layers = &mut map.layers;
+
+  Grid::new(&mut map.tiles, map.width).blit(&Stamp::from(&room), |idx, existing, tile| {
+    // if this tile overlaps, 9 = clash
+    let tile = if is_new_room && existing == 8 { 9 } else { tile };
+    layers.set_structure(idx, tile, node_idx);
+    tile
+  });
 
   // println!("Add room: node #{}", node_idx);
   // debug::print_map(map.tiles.clone(), map.width);
@@ -64,60 +77,52 @@ pub fn add_or_update_room(map: &mut Map, node_idx: usize, room: Room) {
   map.rooms.insert(node_idx, room);
 }
 
+/// Why a room can't be placed on the map
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Conflict {
+  /// Part of the room lies off the map
+  OutOfRange,
+  /// A room tile lands on another room's tile, at this map tile index
+  Overlap(usize),
+  /// The room, or the door it connects through, crosses the edge of the map
+  CrossesEdge,
+}
+
 /// Try and see if this valid position can fit on the map
 pub fn can_place_room(map: &Map, room: &Room, door_type: u8) -> bool {
-  // start at x,y in the map
-  let mut idx = xy_idx(room.x, room.y, map.width);
-  let mut x = 1;
-  let mut can_place = true;
-  let mut test_area = map.tiles.clone();
-
-  for tile in room.tiles.iter() {
-    let in_range = idx < map.tiles.len();
-    let is_tile = *tile != 0;
-    let tile_overlaps_another = in_range && is_tile && map.tiles[idx] != 0;
-    let crosses_side_of_map = room_crosses_side_of_map(idx, *tile, door_type, map.width as usize, map.tiles.len());
-
-    let has_conflicts = !in_range || tile_overlaps_another || crosses_side_of_map;
-
-    // if this tile overlaps
-    if has_conflicts {
-      // Debug: print every tile conflict with a red mark + reason
-      if DEBUG {
-        let reason = match (!in_range, tile_overlaps_another, crosses_side_of_map) {
-          (true, false, false) => "Out of range".to_string(),
-          (false, true, false) => format!("Tile overlaps another: {} {}", *tile, map.tiles[idx]),
-          (false, false, true) => "Room crosses the map edge".to_string(),
-          _ => "".to_string(),
-        };
-
-        test_area[idx] = 9;
-        println!("CONFLICT - i:{}, reason:{}", idx, reason);
-        debug::print_map(test_area.clone(), map.width);
-      }
-      // end debug
-
-      can_place = false;
-      break;
-    } else {
-      test_area[idx] = *tile;
-    }
+  room_conflict(map, room, door_type).is_none()
+}
 
-    // end of the row, move down one and back to the left
-    if x == room.template.w {
-      x = 1;
-      idx += (map.width - room.template.w + 1) as usize;
-    } else {
-      idx += 1;
-      x += 1;
-    }
-  }
+/// The first reason the room can't be placed on the map, if any
+pub fn room_conflict(map: &Map, room: &Room, door_type: u8) -> Option<Conflict> {
+  let grid = Grid::new(&map.tiles, map.width);
+  let stamp = Stamp::from(room);
+
+  let conflict = if !grid.contains(&stamp) {
+    Some(Conflict::OutOfRange)
+  } else if let Some(idx) = grid.overlap(&stamp, |tile| tile != 0) {
+    Some(Conflict::Overlap(idx))
+  } else if stamp.cells().any(|(x, y, tile)| {
+    let idx = grid.index(x, y).unwrap();
+    room_crosses_side_of_map(idx, tile, door_type, map.width as usize, map.tiles.len())
+  }) {
+    Some(Conflict::CrossesEdge)
+  } else {
+    None
+  };
 
-  if can_place && DEBUG {
-    debug::print_map(test_area.clone(), map.width);
+  // Debug: print the room on the map, marking overlaps red, with the reason for any conflict
+  if DEBUG {
+    let mut test_area = Grid::new(map.tiles.clone(), map.width);
+    test_area.blit(&stamp, |_, existing, tile| if existing != 0 { 9 } else { tile });
+
+    if let Some(conflict) = conflict {
+      println!("CONFLICT - {:?}", conflict);
+    }
+    debug::print_map(test_area.tiles().to_vec(), map.width);
   }
 
-  can_place
+  conflict
 }
 
 /// Check if the door or the background crosses the edge of the map
@@ -130,7 +135,7 @@ fn room_crosses_side_of_map(idx: usize, tile: u8, door_type: u8, width: usize, l
   // 5 - west door
 
   // east
-  if (idx + 1) % width == 0 {
+  if (idx + 1).is_multiple_of(width) {
     sides.push(3);
   // west
   } else if (idx + 1) % width == 1 {
@@ -147,7 +152,7 @@ fn room_crosses_side_of_map(idx: usize, tile: u8, door_type: u8, width: usize, l
     sides.push(4);
   };
 
-  if sides.len() > 0 {
+  if !sides.is_empty() {
     // Intended door crosses the map edge
     if sides.contains(&door_type) {
       return true;
@@ -164,7 +169,7 @@ fn room_crosses_side_of_map(idx: usize, tile: u8, door_type: u8, width: usize, l
 
 pub fn find_or_create_start_room(map: &mut Map, templates: &mut RoomTemplates, idx: &usize) -> Room {
   let start_room_templates = templates.of_type(RoomType::Start);
-  let (_, start_room_template) = start_room_templates.get(0).unwrap().clone();
+  let (_, start_room_template) = *start_room_templates.first().unwrap();
   let start_room = Room::new(start_room_template.clone());
 
   let found_room = map.rooms.get(idx).unwrap_or(&start_room);
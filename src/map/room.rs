@@ -1,24 +1,21 @@
 use super::{
   graph::NeighbourMap,
+  room_templates,
   room_templates::{RoomCombination, RoomTemplate},
   xy_idx, XY,
 };
+use crate::traversal::DoorLink;
 use petgraph::stable_graph::NodeIndex;
 use std::collections::HashMap;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Default)]
 pub enum RoomType {
   Start,
+  #[default]
   Normal,
   Boss,
 }
 
-impl Default for RoomType {
-  fn default() -> RoomType {
-    RoomType::Normal
-  }
-}
-
 #[derive(Default, Clone)]
 pub struct Room {
   pub x: i32,
@@ -28,6 +25,8 @@ pub struct Room {
   pub door_connections: DoorConnections,
   /// The XY coords for each possible door
   pub tiles: Vec<u8>,
+  /// How the player can travel between each pair of used doors
+  pub door_links: Vec<DoorLink>,
 }
 
 impl Room {
@@ -40,11 +39,21 @@ impl Room {
   }
 
   pub fn add_door(&mut self, node_a_idx: usize, node_b_idx: usize, door_type: u8, xy: XY, dir: bool) {
+    self.connect_door(node_a_idx, node_b_idx, door_type, xy, dir, false);
+  }
+
+  /// Add a door hidden behind a breakable wall
+  pub fn add_secret_door(&mut self, node_a_idx: usize, node_b_idx: usize, door_type: u8, xy: XY, dir: bool) {
+    self.connect_door(node_a_idx, node_b_idx, door_type, xy, dir, true);
+  }
+
+  fn connect_door(&mut self, node_a_idx: usize, node_b_idx: usize, door_type: u8, xy: XY, dir: bool, secret: bool) {
     let connection = DoorConnection {
       node_a_idx,
       node_b_idx,
       xy,
       direction: dir,
+      secret,
     };
 
     if let Some(doors) = self.door_connections.get_mut(&door_type) {
@@ -55,8 +64,128 @@ impl Room {
 
     let idx = xy_idx(xy.0 - 1, xy.1 - 1, self.template.w);
 
-    // Assign a connected door index
-    self.tiles[idx] = 7;
+    // Assign a connected door index, 6 for a secret door
+    self.tiles[idx] = if secret { 6 } else { 7 };
+  }
+
+  /// How many more doors this room could use, once the door at xy is also used
+  pub fn free_door_slots_after(&self, xy: XY) -> u32 {
+    let mut tiles = self.tiles.clone();
+    tiles[xy_idx(xy.0 - 1, xy.1 - 1, self.template.w)] = 7;
+
+    let slots = room_templates::door_slots(&self.template.possible_doors_xy, &tiles, self.template.w);
+    let used_doors = self.door_connections.values().map(|doors| doors.len() as u32).sum::<u32>() + 1;
+
+    match self.template.max_doors {
+      Some(max) => slots.min(max.saturating_sub(used_doors)),
+      None => slots,
+    }
+  }
+
+  /// Position and size of the room on the map
+  pub fn bounds(&self) -> Bounds {
+    Bounds {
+      x: self.x,
+      y: self.y,
+      w: self.template.w,
+      h: self.template.h,
+    }
+  }
+
+  /// Every connected door in map tile coordinates, with the room it leads to
+  pub fn doors_world(&self) -> Vec<WorldDoor> {
+    let mut doors: Vec<WorldDoor> = self
+      .door_connections
+      .iter()
+      .flat_map(|(door_type, connections)| {
+        connections.iter().map(|connection| {
+          let (x, y) = connection.world_xy(self);
+          WorldDoor {
+            x,
+            y,
+            door_type: *door_type,
+            target: connection.node_b_idx,
+            direction: connection.direction,
+            secret: connection.secret,
+          }
+        })
+      })
+      .collect();
+    doors.sort_by_key(|door| (door.y, door.x));
+    doors
+  }
+
+  /// Possible doors that aren't connected yet, as door type and map tile coordinates
+  pub fn free_doors(&self) -> Vec<(u8, XY)> {
+    self
+      .tiles
+      .iter()
+      .enumerate()
+      .filter(|(_, tile)| (2..=5).contains(*tile))
+      .map(|(idx, tile)| (*tile, self.world_xy(idx)))
+      .collect()
+  }
+
+  /// Map tile coordinates of the open space inside the walls, including ladders and one way platforms
+  pub fn interior_tiles(&self) -> Vec<XY> {
+    self
+      .tiles
+      .iter()
+      .enumerate()
+      .filter(|(_, tile)| matches!(tile, 8 | 11 | 12))
+      .map(|(idx, _)| self.world_xy(idx))
+      .collect()
+  }
+
+  /// Map tile coordinates of an index into the room's tiles
+  fn world_xy(&self, idx: usize) -> XY {
+    (self.x + idx as i32 % self.template.w, self.y + idx as i32 / self.template.w)
+  }
+}
+
+/// A room's rectangle in map tile coordinates
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bounds {
+  pub x: i32,
+  pub y: i32,
+  pub w: i32,
+  pub h: i32,
+}
+
+impl Bounds {
+  pub fn contains(&self, x: i32, y: i32) -> bool {
+    x >= self.x && y >= self.y && x < self.x + self.w && y < self.y + self.h
+  }
+
+  pub fn centre(&self) -> (f64, f64) {
+    (self.x as f64 + self.w as f64 / 2.0, self.y as f64 + self.h as f64 / 2.0)
+  }
+}
+
+/// A connected door in map tile coordinates
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WorldDoor {
+  pub x: i32,
+  pub y: i32,
+  /// 2 - north, 3 - east, 4 - south, 5 - west
+  pub door_type: u8,
+  /// Node index of the room on the other side
+  pub target: usize,
+  /// Direction of the connection - true = A->B | false = B->A
+  pub direction: bool,
+  pub secret: bool,
+}
+
+impl WorldDoor {
+  /// The door type and map tile coordinates of a door that connects to this one, eg: a south door directly above a
+  /// north door
+  pub fn facing(&self) -> (u8, XY) {
+    match self.door_type {
+      2 => (4, (self.x, self.y - 1)),
+      3 => (5, (self.x + 1, self.y)),
+      4 => (2, (self.x, self.y + 1)),
+      _ => (3, (self.x - 1, self.y)),
+    }
   }
 }
 
@@ -71,6 +200,15 @@ pub struct DoorConnection {
   xy: XY,
   /// Direction of the connection - true = A->B | false = B->A
   pub direction: bool,
+  /// Hidden behind a breakable wall, leading to a secret room
+  pub secret: bool,
+}
+
+impl DoorConnection {
+  /// Map tile coordinates of the door, given the room it belongs to
+  pub fn world_xy(&self, room: &Room) -> XY {
+    (room.x + self.xy.0 - 1, room.y + self.xy.1 - 1)
+  }
 }
 
 /// Hashmap of each door type and their connections
@@ -79,7 +217,15 @@ pub type DoorsXY = HashMap<u8, Vec<XY>>;
 
 /// Modify room_b's combination coordinates relative to room_a
 pub fn align_room_b(room_a: &Room, room_b: &mut Room, combination: RoomCombination) -> Room {
-  let ((room_b_x, room_b_y), _, _, door_b_type, _) = combination;
+  // move room b to a valid connecting position
+  (room_b.x, room_b.y) = room_b_xy(room_a, &combination);
+
+  room_b.clone()
+}
+
+/// Map coordinates room B would have if it joined room A through this combination
+pub fn room_b_xy(room_a: &Room, combination: &RoomCombination) -> XY {
+  let ((room_b_x, room_b_y), _, _, door_b_type, _) = *combination;
 
   let (x_offset, y_offset) = match door_b_type {
     2 => (0, -1),
@@ -89,17 +235,13 @@ pub fn align_room_b(room_a: &Room, room_b: &mut Room, combination: RoomCombinati
     _ => (0, 0),
   };
 
-  // move room b to a valid connecting position
-  room_b.x = room_a.x - (room_b_x + x_offset);
-  room_b.y = room_a.y - (room_b_y + y_offset);
-
-  room_b.clone()
+  (room_a.x - (room_b_x + x_offset), room_a.y - (room_b_y + y_offset))
 }
 
-///
+/// List the neighbours of a node as (node, neighbour, is_outbound)
 pub fn get_neighbours(node_idx: &usize, neighbour_map: &NeighbourMap) -> Vec<(NodeIndex, NodeIndex, bool)> {
   let empty = Vec::new();
-  let neighbours = neighbour_map.get(&node_idx).unwrap_or(&empty);
+  let neighbours = neighbour_map.get(node_idx).unwrap_or(&empty);
 
   neighbours
     .iter()
@@ -0,0 +1,129 @@
+use super::{
+  room_template_definitions::{calculate_door_tiles, calculate_doors_xy},
+  room_templates::RoomTemplate,
+};
+use std::ops::RangeInclusive;
+
+/// Minimum thickness of a room arm: a wall either side of one interior tile
+const MIN_ARM: i32 = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RoomShape {
+  Rectangle,
+  /// Vertical arm on the left joined to a horizontal arm along the bottom
+  L,
+  /// Horizontal bar along the top with a centred stem
+  T,
+  /// Two vertical arms joined along the bottom
+  U,
+  Plus,
+}
+
+/// Procedurally generates a room template of a shape for every size within the width and height ranges
+pub struct RoomShapeGenerator {
+  pub shape: RoomShape,
+  pub widths: RangeInclusive<i32>,
+  pub heights: RangeInclusive<i32>,
+}
+
+impl RoomShapeGenerator {
+  pub fn new(shape: RoomShape, widths: RangeInclusive<i32>, heights: RangeInclusive<i32>) -> RoomShapeGenerator {
+    RoomShapeGenerator { shape, widths, heights }
+  }
+
+  /// Sizes too small to form the shape are skipped
+  pub fn templates(&self) -> Vec<RoomTemplate> {
+    let mut templates = vec![];
+
+    for width in self.widths.clone() {
+      for height in self.heights.clone() {
+        if let Some(template) = generate(self.shape, width, height) {
+          templates.push(template);
+        }
+      }
+    }
+
+    templates
+  }
+}
+
+/// Build a room template from a shape, walling in the footprint and placing possible doors along its perimeter
+pub fn generate(shape: RoomShape, width: i32, height: i32) -> Option<RoomTemplate> {
+  // Arm thickness grows with the room size so bigger rooms have wider corridors
+  let arm_w = MIN_ARM.max(width / 3);
+  let arm_h = MIN_ARM.max(height / 3);
+  let stem_x = (width - arm_w) / 2;
+  let bar_y = (height - arm_h) / 2;
+
+  let (fits, min_doors) = match shape {
+    RoomShape::Rectangle => (width >= MIN_ARM && height >= MIN_ARM, 0),
+    RoomShape::L => (width >= arm_w + MIN_ARM && height >= arm_h + MIN_ARM, 0),
+    RoomShape::T => (width >= arm_w + 4 && height >= arm_h + MIN_ARM, 3),
+    RoomShape::U => (width > arm_w * 2 && height >= arm_h + MIN_ARM, 2),
+    RoomShape::Plus => (width >= arm_w + 4 && height >= arm_h + 4, 2),
+  };
+
+  if !fits {
+    return None;
+  }
+
+  let in_footprint = |x: i32, y: i32| -> bool {
+    if x < 0 || y < 0 || x >= width || y >= height {
+      return false;
+    }
+
+    let in_stem = x >= stem_x && x < stem_x + arm_w;
+    let in_bar = y >= bar_y && y < bar_y + arm_h;
+
+    match shape {
+      RoomShape::Rectangle => true,
+      RoomShape::L => x < arm_w || y >= height - arm_h,
+      RoomShape::T => y < arm_h || in_stem,
+      RoomShape::U => x < arm_w || x >= width - arm_w || y >= height - arm_h,
+      RoomShape::Plus => in_stem || in_bar,
+    }
+  };
+
+  // 0 outside the footprint, 1 on its edge, 8 inside
+  let tile_at = |x: i32, y: i32| -> u8 {
+    if !in_footprint(x, y) {
+      return 0;
+    }
+
+    let on_edge = (-1..=1).any(|dy| (-1..=1).any(|dx| !in_footprint(x + dx, y + dy)));
+    if on_edge {
+      1
+    } else {
+      8
+    }
+  };
+
+  let mut template = vec![];
+
+  for y in 0..height {
+    for x in 0..width {
+      let tile = tile_at(x, y);
+      // A wall becomes a possible door when it leads straight from the exterior into the interior
+      let is_door = tile == 1
+        && [(0, -1), (1, 0), (0, 1), (-1, 0)]
+          .iter()
+          .filter(|(dx, dy)| tile_at(x + dx, y + dy) == 0 && tile_at(x - dx, y - dy) == 8)
+          .count()
+          == 1;
+
+      template.push(if is_door { 2 } else { tile });
+    }
+  }
+
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  Some(RoomTemplate {
+    h: height,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    min_doors,
+    ..Default::default()
+  })
+}
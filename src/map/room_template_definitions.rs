@@ -2,7 +2,7 @@ use super::{room_templates::RoomTemplate, DoorsXY, RoomType};
 use std::collections::HashMap;
 
 /// Iterate through each tile and push the XY coords if its a door (2,2,2,5)
-fn calculate_doors_xy(tiles: &Vec<u8>, width: i32) -> DoorsXY {
+pub fn calculate_doors_xy(tiles: &Vec<u8>, width: i32) -> DoorsXY {
   let door_tiles: Vec<u8> = vec![2, 3, 4, 5];
   let mut doors_xy: DoorsXY = HashMap::new();
   let mut x = 1;
@@ -31,7 +31,7 @@ fn calculate_doors_xy(tiles: &Vec<u8>, width: i32) -> DoorsXY {
 }
 
 /// Takes any door reference on a room layout and converts it to a door number
-fn calculate_door_tiles(tiles: Vec<u8>, width: i32) -> Vec<u8> {
+pub fn calculate_door_tiles(tiles: Vec<u8>, width: i32) -> Vec<u8> {
   // 2 - possible north door
   // 3 - possible east door
   // 4 - possible south door
@@ -68,7 +68,7 @@ fn calculate_door_tiles(tiles: Vec<u8>, width: i32) -> Vec<u8> {
   tiles_with_doors
 }
 
-fn in_range<T>(arr: &Vec<T>, idx: i32) -> bool {
+fn in_range<T>(arr: &[T], idx: i32) -> bool {
   idx >= 0 && idx < arr.len() as i32
 }
 
@@ -144,6 +144,7 @@ pub fn big_square() -> RoomTemplate {
     w: width,
     tiles,
     possible_doors_xy: doors_xy,
+    tags: vec!["arena".to_string()],
     ..Default::default()
   }
 }
@@ -308,6 +309,7 @@ pub fn long_shape() -> RoomTemplate {
     w: width,
     tiles,
     possible_doors_xy: doors_xy,
+    tags: vec!["horizontal".to_string()],
     ..Default::default()
   }
 }
@@ -331,6 +333,7 @@ pub fn cross() -> RoomTemplate {
     tiles,
     possible_doors_xy: doors_xy,
     min_doors: 2,
+    min_repeat_distance: Some(3),
     ..Default::default()
   }
 }
@@ -357,6 +360,8 @@ pub fn tall() -> RoomTemplate {
     w: width,
     tiles,
     possible_doors_xy: doors_xy,
+    min_repeat_distance: Some(2),
+    tags: vec!["vertical".to_string()],
     ..Default::default()
   }
 }
@@ -378,6 +383,7 @@ pub fn wide() -> RoomTemplate {
     w: width,
     tiles,
     possible_doors_xy: doors_xy,
+    tags: vec!["horizontal".to_string()],
     ..Default::default()
   }
 }
@@ -407,6 +413,218 @@ pub fn jar() -> RoomTemplate {
     tiles,
     possible_doors_xy: doors_xy,
     min_doors: 2,
+    min_repeat_distance: Some(2),
+    tags: vec!["vertical".to_string()],
+    ..Default::default()
+  }
+}
+
+pub fn treasure_vault() -> RoomTemplate {
+  let template = vec![
+    0, 1, 1, 1, 1, 1, 0, //
+    1, 1, 8, 8, 8, 1, 1, //
+    2, 8, 8, 8, 8, 8, 2, //
+    1, 1, 1, 1, 1, 1, 1,
+  ];
+
+  let width: i32 = 7;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    weight: 1,
+    max_per_map: Some(1),
+    tags: vec!["treasure".to_string()],
+    max_doors: Some(2),
+    ..Default::default()
+  }
+}
+
+pub fn save_room() -> RoomTemplate {
+  let template = vec![
+    1, 1, 2, 1, 1, //
+    1, 8, 8, 8, 1, //
+    2, 8, 8, 8, 2, //
+    1, 1, 2, 1, 1,
+  ];
+
+  let width: i32 = 5;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    tags: vec!["save".to_string()],
+    max_doors: Some(2),
+    ..Default::default()
+  }
+}
+
+pub fn secret_alcove() -> RoomTemplate {
+  let template = vec![
+    1, 1, 2, 1, 1, //
+    2, 8, 8, 8, 2, //
+    1, 1, 1, 1, 1,
+  ];
+
+  let width: i32 = 5;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    tags: vec!["secret".to_string()],
+    max_doors: Some(1),
+    ..Default::default()
+  }
+}
+
+pub fn secret_cellar() -> RoomTemplate {
+  let template = vec![
+    1, 1, 2, 2, 1, 1, //
+    2, 8, 8, 8, 8, 2, //
+    1, 8, 8, 8, 8, 1, //
+    1, 1, 1, 1, 1, 1,
+  ];
+
+  let width: i32 = 6;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    weight: 5,
+    tags: vec!["secret".to_string()],
+    max_doors: Some(1),
+    ..Default::default()
+  }
+}
+
+pub fn shop() -> RoomTemplate {
+  let template = vec![
+    1, 1, 1, 2, 1, 1, 1, //
+    1, 8, 8, 8, 8, 8, 1, //
+    2, 8, 8, 8, 8, 8, 2, //
+    1, 1, 1, 1, 1, 1, 1,
+  ];
+
+  let width: i32 = 7;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    tags: vec!["shop".to_string()],
+    max_doors: Some(2),
+    ..Default::default()
+  }
+}
+
+pub fn teleport() -> RoomTemplate {
+  let template = vec![
+    0, 1, 1, 2, 1, 1, 0, //
+    1, 1, 8, 8, 8, 1, 1, //
+    2, 8, 8, 8, 8, 8, 2, //
+    1, 1, 1, 2, 1, 1, 1,
+  ];
+
+  let width: i32 = 7;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    tags: vec!["teleport".to_string()],
+    ..Default::default()
+  }
+}
+
+pub fn locked_gate() -> RoomTemplate {
+  let template = vec![
+    1, 1, 1, 1, 1, 1, 1, //
+    1, 8, 8, 1, 8, 8, 1, //
+    2, 8, 8, 1, 8, 8, 2, //
+    2, 8, 8, 8, 8, 8, 2, //
+    1, 1, 1, 1, 1, 1, 1,
+  ];
+
+  let width: i32 = 7;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    tags: vec!["lock".to_string()],
+    max_doors: Some(2),
+    ..Default::default()
+  }
+}
+
+pub fn key_shrine() -> RoomTemplate {
+  let template = vec![
+    1, 1, 2, 1, 1, //
+    1, 8, 8, 8, 1, //
+    2, 8, 8, 8, 2, //
+    2, 8, 8, 8, 2, //
+    1, 1, 1, 1, 1,
+  ];
+
+  let width: i32 = 5;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    tags: vec!["key".to_string()],
+    max_doors: Some(2),
+    ..Default::default()
+  }
+}
+
+pub fn shortcut_passage() -> RoomTemplate {
+  let template = vec![
+    1, 2, 1, 1, 1, 1, 1, 2, 1, //
+    2, 8, 8, 8, 8, 8, 8, 8, 2, //
+    2, 8, 8, 8, 8, 8, 8, 8, 2, //
+    1, 2, 1, 1, 1, 1, 1, 2, 1,
+  ];
+
+  let width: i32 = 9;
+  let tiles = calculate_door_tiles(template, width);
+  let doors_xy = calculate_doors_xy(&tiles, width);
+
+  RoomTemplate {
+    h: tiles.len() as i32 / width,
+    w: width,
+    tiles,
+    possible_doors_xy: doors_xy,
+    tags: vec!["shortcut".to_string()],
+    max_doors: Some(2),
     ..Default::default()
   }
 }
@@ -453,6 +671,7 @@ pub fn boss_room() -> RoomTemplate {
     tiles,
     possible_doors_xy: doors_xy,
     room_type: RoomType::Boss,
+    tags: vec!["arena".to_string()],
     ..Default::default()
   }
 }
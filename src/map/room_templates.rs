@@ -1,9 +1,23 @@
-use super::{room_template_definitions as definitions, DoorsXY, RoomType, XY};
+use super::{
+  combination_cache,
+  grid::{Grid, Stamp},
+  room_shapes::{RoomShape, RoomShapeGenerator},
+  room_template_definitions as definitions, DoorsXY, RoomType, XY,
+};
 use rand::Rng;
+use rayon::prelude::*;
 use std::collections::HashMap;
+use std::path::Path;
 
-#[derive(Default, Clone)]
+/// Selection weight of a template unless it is made more common or rarer
+pub const DEFAULT_WEIGHT: u32 = 10;
+/// Templates with these tags are only used by nodes whose role asks for them
+pub const RESERVED_TAGS: [&str; 7] = ["save", "shop", "teleport", "secret", "lock", "key", "shortcut"];
+
+#[derive(Clone)]
 pub struct RoomTemplate {
+  /// Index of the template in RoomTemplates
+  pub id: usize,
   /// The XY coords for each possible door
   pub h: i32,
   pub w: i32,
@@ -12,14 +26,113 @@ pub struct RoomTemplate {
   pub room_type: RoomType,
   pub tiles: Vec<u8>,
   pub valid_combinations: HashMap<usize, Vec<RoomCombination>>,
+  /// Relative chance of being selected, compared with DEFAULT_WEIGHT
+  pub weight: u32,
+  /// Maximum number of rooms using this template in a single map
+  pub max_per_map: Option<u32>,
+  /// Minimum graph distance between two rooms using this template
+  pub min_repeat_distance: Option<usize>,
+  /// Free-form labels matched against node roles (eg: shop, save, vertical, arena)
+  pub tags: Vec<String>,
+  /// Caps how many doors can be used, below what the room's door faces could fit
+  pub max_doors: Option<u32>,
+}
+
+impl Default for RoomTemplate {
+  fn default() -> RoomTemplate {
+    RoomTemplate {
+      id: 0,
+      h: 0,
+      w: 0,
+      min_doors: 0,
+      possible_doors_xy: HashMap::new(),
+      room_type: RoomType::default(),
+      tiles: vec![],
+      valid_combinations: HashMap::new(),
+      weight: DEFAULT_WEIGHT,
+      max_per_map: None,
+      min_repeat_distance: None,
+      tags: vec![],
+      max_doors: None,
+    }
+  }
+}
+
+impl RoomTemplate {
+  /// Has all of the required tags, and no reserved tag that wasn't asked for
+  pub fn matches_tags(&self, required_tags: &[String]) -> bool {
+    let has_required = required_tags.iter().all(|tag| self.tags.contains(tag));
+    let has_unrequested_reserved = self
+      .tags
+      .iter()
+      .any(|tag| RESERVED_TAGS.contains(&tag.as_str()) && !required_tags.contains(tag));
+
+    has_required && !has_unrequested_reserved
+  }
+
+  /// How many doors can be used at the same time
+  pub fn door_capacity(&self) -> u32 {
+    let capacity = door_slots(&self.possible_doors_xy, &self.tiles, self.w);
+    self.max_doors.map_or(capacity, |max| capacity.min(max))
+  }
 }
 
-///
+/// Count the doors that could still be used. Doors on the same face need a wall between them, so each run of adjacent
+/// door tiles fits a door on every other tile. Used doors (7) and the tiles beside them are skipped
+pub fn door_slots(doors_xy: &DoorsXY, tiles: &[u8], width: i32) -> u32 {
+  let tile_at = |(x, y): XY| -> u8 {
+    if x < 1 || y < 1 || x > width {
+      return 0;
+    }
+    *tiles.get(((y - 1) * width + x - 1) as usize).unwrap_or(&0)
+  };
+
+  let mut slots = 0;
+
+  for xys in doors_xy.values() {
+    let mut available: Vec<XY> = xys
+      .iter()
+      .filter(|(x, y)| {
+        let beside_used_door = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+          .iter()
+          .any(|(dx, dy)| tile_at((x + dx, y + dy)) == 7);
+        tile_at((*x, *y)) != 7 && !beside_used_door
+      })
+      .cloned()
+      .collect();
+
+    available.sort_by_key(|(x, y)| (*y, *x));
+
+    // Group into runs of adjacent door tiles
+    let mut run_length: u32 = 0;
+    let mut previous: Option<XY> = None;
+
+    for xy in available.iter() {
+      let is_adjacent = previous.is_some_and(|(px, py)| (xy.0 - px).abs() + (xy.1 - py).abs() == 1);
+
+      if !is_adjacent {
+        slots += run_length.div_ceil(2);
+        run_length = 0;
+      }
+
+      run_length += 1;
+      previous = Some(*xy);
+    }
+
+    slots += run_length.div_ceil(2);
+  }
+
+  slots
+}
+
+/// Params: Room B xy relative to room A, door A type, door A xy, door B type, door B xy
 pub type RoomCombination = (XY, u8, XY, u8, XY);
+/// Valid combinations of every template A against every template B
+pub type CombinationTable = HashMap<usize, HashMap<usize, Vec<RoomCombination>>>;
 /// Hashmap of each room template
-type RoomTemplateMap = HashMap<usize, RoomTemplate>;
-/// Params: Tiles with room places, x, y coordinates of the room
-type TemporaryCanvas = (Vec<u8>, i32, i32);
+pub type RoomTemplateMap = HashMap<usize, RoomTemplate>;
+/// Params: Canvas with the room placed, x, y coordinates of the room
+type TemporaryCanvas = (Grid<Vec<u8>>, i32, i32);
 
 pub struct RoomTemplates {
   pub rooms: RoomTemplateMap,
@@ -27,33 +140,31 @@ pub struct RoomTemplates {
 
 impl RoomTemplates {
   pub fn new() -> RoomTemplates {
-    let mut rooms: RoomTemplateMap = HashMap::new();
-
-    for (num, template) in Vec::from([
-      definitions::bent_l(),
-      definitions::bent_r(),
-      definitions::big_square(),
-      definitions::boss_room(),
-      definitions::cross(),
-      definitions::jar(),
-      definitions::l_bottom_left_shape(),
-      definitions::l_bottom_right_shape(),
-      definitions::l_top_left_shape(),
-      definitions::l_top_right_shape(),
-      definitions::long_shape(),
-      definitions::rectangle(),
-      definitions::small_square(),
-      definitions::start_room(),
-      definitions::tall(),
-      definitions::wide(),
-    ])
-    .iter()
-    .enumerate()
-    {
-      rooms.insert(num, template.clone());
-    }
+    let mut rooms = template_definitions();
+    let combinations = combination_table(&rooms);
+    apply_combinations(&mut rooms, combinations);
+
+    RoomTemplates { rooms }
+  }
+
+  /// Use the cached combinations if they were calculated for this exact template set,
+  /// otherwise calculate them and refresh the cache
+  pub fn load_or_calculate(cache_path: &Path) -> RoomTemplates {
+    let mut rooms = template_definitions();
+    let hash = combination_cache::template_hash(&rooms);
 
-    calculate_combinations(&mut rooms);
+    let combinations = match combination_cache::load(cache_path, hash) {
+      Some(combinations) => combinations,
+      None => {
+        let combinations = combination_table(&rooms);
+        if let Err(error) = combination_cache::save(cache_path, hash, &combinations) {
+          println!("Unable to save combination cache: {}", error);
+        }
+        combinations
+      }
+    };
+
+    apply_combinations(&mut rooms, combinations);
 
     RoomTemplates { rooms }
   }
@@ -67,6 +178,32 @@ impl RoomTemplates {
       .clone()
   }
 
+  /// The most doors any normal room can host, for nodes with more neighbours than any template fits
+  pub fn max_door_capacity(&self) -> u32 {
+    self
+      .of_type(RoomType::Normal)
+      .iter()
+      .map(|(_, template)| template.door_capacity())
+      .max()
+      .unwrap_or(0)
+  }
+
+  /// The most doors a room is sure to host whichever type it is, the least of each type's most doors
+  pub fn guaranteed_door_capacity(&self) -> u32 {
+    [RoomType::Start, RoomType::Normal, RoomType::Boss]
+      .into_iter()
+      .map(|room_type| {
+        self
+          .of_type(room_type)
+          .iter()
+          .map(|(_, template)| template.door_capacity())
+          .max()
+          .unwrap_or(0)
+      })
+      .min()
+      .unwrap_or(0)
+  }
+
   pub fn _random(&self) -> RoomTemplate {
     let mut rng = rand::thread_rng();
     let rooms_len = self.rooms.len();
@@ -75,72 +212,139 @@ impl RoomTemplates {
   }
 }
 
+fn template_definitions() -> RoomTemplateMap {
+  let mut rooms: RoomTemplateMap = HashMap::new();
+
+  let generated = [
+    RoomShapeGenerator::new(RoomShape::Rectangle, 7..=8, 4..=6),
+    RoomShapeGenerator::new(RoomShape::L, 6..=7, 6..=7),
+    RoomShapeGenerator::new(RoomShape::T, 9..=9, 6..=7),
+    RoomShapeGenerator::new(RoomShape::U, 9..=9, 6..=6),
+    RoomShapeGenerator::new(RoomShape::Plus, 9..=9, 9..=9),
+  ];
+
+  let hand_drawn = Vec::from([
+    definitions::bent_l(),
+    definitions::bent_r(),
+    definitions::big_square(),
+    definitions::boss_room(),
+    definitions::cross(),
+    definitions::jar(),
+    definitions::key_shrine(),
+    definitions::l_bottom_left_shape(),
+    definitions::l_bottom_right_shape(),
+    definitions::l_top_left_shape(),
+    definitions::l_top_right_shape(),
+    definitions::locked_gate(),
+    definitions::long_shape(),
+    definitions::rectangle(),
+    definitions::save_room(),
+    definitions::secret_alcove(),
+    definitions::secret_cellar(),
+    definitions::shop(),
+    definitions::shortcut_passage(),
+    definitions::small_square(),
+    definitions::start_room(),
+    definitions::tall(),
+    definitions::teleport(),
+    definitions::treasure_vault(),
+    definitions::wide(),
+  ]);
+
+  let templates = hand_drawn
+    .into_iter()
+    .chain(generated.iter().flat_map(|generator| generator.templates()));
+
+  for (num, mut template) in templates.enumerate() {
+    template.id = num;
+    rooms.insert(num, template);
+  }
+
+  rooms
+}
+
 pub fn get(idx: &usize, rooms: &mut RoomTemplates) -> RoomTemplate {
   rooms.rooms.get(idx).unwrap().clone()
 }
 
-/// precalculate valid XY positions of all door combinations, these get randomly used when crawling through the original level graph
-fn calculate_combinations(templates: &mut RoomTemplateMap) {
-  let templates_b = templates.clone();
-  // For each template
-  for (_, template_a) in templates.iter_mut() {
-    // Try all templates
-    for (tb_i, template_b) in templates_b.iter() {
-      // Create a placeholder array
-      if !template_a.valid_combinations.contains_key(tb_i) {
-        template_a.valid_combinations.insert(*tb_i, Vec::new());
+/// Randomly order template indexes, biased by each template's weight (Efraimidis-Spirakis sampling).
+/// More likely templates are ordered first
+pub fn weighted_shuffle<R: Rng>(idxs: Vec<usize>, templates: &RoomTemplates, rng: &mut R) -> Vec<usize> {
+  let mut keyed: Vec<(f64, usize)> = idxs
+    .into_iter()
+    .filter_map(|idx| {
+      let weight = templates.rooms.get(&idx)?.weight;
+      if weight == 0 {
+        return None;
       }
+      // u^(1/w) sorts heavier weights towards 1
+      let key = rng.gen::<f64>().powf(1.0 / weight as f64);
+      Some((key, idx))
+    })
+    .collect();
+
+  keyed.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap());
+  keyed.into_iter().map(|(_, idx)| idx).collect()
+}
 
-      // For each door template A
-      for (door_a_type, door_xys) in template_a.possible_doors_xy.iter() {
-        // For each door position on each face
-        for door_a_xy in door_xys {
-          // Get the corresponding door (eg, left door for right door)
-          let door_b_type = connecting_door(*door_a_type);
-          // Try each corresponding door of template B
-          if let Some(doors_xy) = template_b.possible_doors_xy.get(&door_b_type) {
-            for door_b_xy in doors_xy {
-              // Align both templates and doors on a canvas and test their compatibility
-              if let Some(template_b_xy) = can_place_room(door_a_xy, door_b_xy, template_a, template_b) {
-                // println!("^ #{}/{} x:{} y:{}", ta_i, tb_i, template_b_x, template_b_y);
-                let combination = (template_b_xy, *door_a_type, *door_a_xy, door_b_type, *door_b_xy);
-
-                if let Some(combo) = template_a.valid_combinations.get_mut(tb_i) {
-                  combo.push(combination);
-                }
-              }
+/// precalculate valid XY positions of all door combinations, these get randomly used when crawling through the original level
+/// graph. Each template A is independent of the others, so they are calculated in parallel
+pub fn combination_table(templates: &RoomTemplateMap) -> CombinationTable {
+  templates
+    .par_iter()
+    .map(|(ta_i, template_a)| (*ta_i, template_combinations(template_a, templates)))
+    .collect()
+}
+
+fn apply_combinations(templates: &mut RoomTemplateMap, mut combinations: CombinationTable) {
+  for (ta_i, template_a) in templates.iter_mut() {
+    template_a.valid_combinations = combinations.remove(ta_i).unwrap_or_default();
+  }
+}
+
+/// All valid door combinations of template A against every template
+fn template_combinations(template_a: &RoomTemplate, templates: &RoomTemplateMap) -> HashMap<usize, Vec<RoomCombination>> {
+  let mut valid_combinations: HashMap<usize, Vec<RoomCombination>> = HashMap::new();
+
+  // Try all templates
+  for (tb_i, template_b) in templates.iter() {
+    // Create a placeholder array
+    let combinations = valid_combinations.entry(*tb_i).or_default();
+
+    // For each door template A
+    for (door_a_type, door_xys) in template_a.possible_doors_xy.iter() {
+      // For each door position on each face
+      for door_a_xy in door_xys {
+        // Get the corresponding door (eg, left door for right door)
+        let door_b_type = connecting_door(*door_a_type);
+        // Try each corresponding door of template B
+        if let Some(doors_xy) = template_b.possible_doors_xy.get(&door_b_type) {
+          for door_b_xy in doors_xy {
+            // Align both templates and doors on a canvas and test their compatibility
+            if let Some(template_b_xy) = can_place_room(door_a_xy, door_b_xy, template_a, template_b) {
+              combinations.push((template_b_xy, *door_a_type, *door_a_xy, door_b_type, *door_b_xy));
             }
-          };
-        }
+          }
+        };
       }
     }
-    // println!("{}: {:?}", ta_i, template_a.valid_combinations);
+
+    // HashMap iteration order is random, keep the cache output stable
+    combinations.sort();
   }
+
+  valid_combinations
 }
 
 /// Place the room into the centre of the canvas
 fn canvas_with_room(room: &RoomTemplate, canvas_w: i32, canvas_h: i32) -> TemporaryCanvas {
-  let mut tiles = vec![0; (canvas_w * canvas_h) as usize];
   let x = (canvas_w / 2) - (room.w / 2);
   let y = (canvas_h / 2) - (room.h / 2);
-  let mut idx = (y * canvas_w + x) as usize;
-  let mut room_x = 1;
-
-  // place each tile in the canvas area
-  for t in &room.tiles {
-    tiles[idx] = *t;
-
-    // end of the row, move down one and back to the left
-    if room_x == room.w {
-      room_x = 1;
-      idx += (canvas_w - room.w + 1) as usize;
-    } else {
-      idx += 1;
-      room_x += 1;
-    }
-  }
 
-  (tiles, x, y)
+  let mut canvas = Grid::new(vec![0; (canvas_w * canvas_h) as usize], canvas_w);
+  canvas.blit(&Stamp::new(x, y, room.w, &room.tiles), |_, _, tile| tile);
+
+  (canvas, x, y)
 }
 
 fn connecting_door(tile: u8) -> u8 {
@@ -153,12 +357,12 @@ fn connecting_door(tile: u8) -> u8 {
   }
 }
 
-/// Refactor: This is basically the same function as map::try_room
+/// Align door b of template B with door a of template A, returning B's position relative to A if B doesn't overlap
+/// A's interior
 fn can_place_room(xy_a: &XY, xy_b: &XY, template_a: &RoomTemplate, template_b: &RoomTemplate) -> Option<XY> {
   let canvas_width = template_a.w + (template_b.w * 2) + 1;
   let canvas_height = template_a.h + (template_b.h * 2) + 1;
-  let (mut canvas_tiles, room_x, room_y) = canvas_with_room(&template_a, canvas_width, canvas_height);
-  let mut can_place = true;
+  let (canvas, room_x, room_y) = canvas_with_room(template_a, canvas_width, canvas_height);
 
   // Get canvas position of door a
   let door_a_x = room_x + xy_a.0;
@@ -166,42 +370,18 @@ fn can_place_room(xy_a: &XY, xy_b: &XY, template_a: &RoomTemplate, template_b: &
 
   // Get canvas position of room b, aligning door a and b together
   // TODO: offset by one
-  let new_room_canvas_x = door_a_x - &xy_b.0;
-  let new_room_canvas_y = door_a_y - &xy_b.1;
+  let new_room_canvas_x = door_a_x - xy_b.0;
+  let new_room_canvas_y = door_a_y - xy_b.1;
 
   // Get relative position of room b to room a
   let new_room_relative_x = room_x - new_room_canvas_x;
   let new_room_relative_y = room_y - new_room_canvas_y;
 
-  // ---------- Duplicated map crawling logic
-  // index of where to begin placing the new room
-  let mut idx = (new_room_canvas_y * canvas_width + new_room_canvas_x) as usize;
-  let mut room_x = 1;
-
-  // check each tile in the canvas area
-  for tile in &template_b.tiles {
-    // if this tile overlaps
-    // thread 'main' panicked at 'index out of bounds
-    if *tile != 0 && canvas_tiles[idx] == 8 {
-      canvas_tiles[idx] = 9; // 9 = clash
-      can_place = false;
-    } else if *tile != 0 {
-      canvas_tiles[idx] = *tile;
-    }
-
-    // end of the row, move down one and back to the left
-    if room_x == template_b.w {
-      room_x = 1;
-      idx += (canvas_width - template_b.w + 1) as usize;
-    } else {
-      idx += 1;
-      room_x += 1;
-    }
-  }
-  // -------- Duplicated map crawling logic
+  // Room b clashes with any tile it places over room a's interior
+  let room_b = Stamp::new(new_room_canvas_x, new_room_canvas_y, template_b.w, &template_b.tiles);
+  let clashes = canvas.overlap(&room_b, |tile| tile == 8).is_some();
 
-  if can_place {
-    // debug::print_map(canvas_tiles.clone(), canvas_width);
+  if canvas.contains(&room_b) && !clashes {
     Some((new_room_relative_x, new_room_relative_y))
   } else {
     None
@@ -0,0 +1,191 @@
+use super::{graph::MapGraph, room::Room, Conflict, Map};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+/// A door that doesn't lead anywhere, in map tile coordinates
+#[derive(Clone, PartialEq, Debug)]
+pub struct DanglingDoor {
+  pub room: usize,
+  pub x: i32,
+  pub y: i32,
+}
+
+/// Every problem found in a level. Rooms are node indexes, pairs are (room, other room)
+#[derive(Clone, Default, Debug)]
+pub struct ValidationReport {
+  pub overlapping_rooms: Vec<(usize, usize)>,
+  /// Rooms off the map or crossing its edges
+  pub rooms_out_of_bounds: Vec<usize>,
+  /// Doors to rooms that aren't on the map or don't line up with a door, and door tiles without a connection
+  pub dangling_doors: Vec<DanglingDoor>,
+  /// Connections the target room has no connection back for
+  pub one_sided_connections: Vec<(usize, usize)>,
+  /// Rooms that can't be walked to from the start room, through secret doors or not
+  pub unreachable_rooms: Vec<usize>,
+  /// Graph edges without a door between their rooms
+  pub unrealised_edges: Vec<(usize, usize)>,
+}
+
+impl ValidationReport {
+  pub fn is_valid(&self) -> bool {
+    self.overlapping_rooms.is_empty()
+      && self.rooms_out_of_bounds.is_empty()
+      && self.dangling_doors.is_empty()
+      && self.one_sided_connections.is_empty()
+      && self.unreachable_rooms.is_empty()
+      && self.unrealised_edges.is_empty()
+  }
+}
+
+impl fmt::Display for ValidationReport {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{} overlapping rooms, {} rooms out of bounds, {} dangling doors, {} one sided connections, {} unreachable rooms, \
+       {} unrealised edges",
+      self.overlapping_rooms.len(),
+      self.rooms_out_of_bounds.len(),
+      self.dangling_doors.len(),
+      self.one_sided_connections.len(),
+      self.unreachable_rooms.len(),
+      self.unrealised_edges.len()
+    )
+  }
+}
+
+/// Check a level, eg: one that was edited by hand, for overlapping or misplaced rooms, broken doors and parts of the
+/// graph that didn't make it onto the map
+pub fn validate(map: &Map, map_graph: &MapGraph) -> ValidationReport {
+  let mut report = ValidationReport::default();
+  let mut node_idxs: Vec<usize> = map.rooms.keys().cloned().collect();
+  node_idxs.sort();
+
+  check_placement(map, &node_idxs, &mut report);
+  check_doors(map, &node_idxs, &mut report);
+
+  let reachable = reachable_rooms(map, map_graph.nodes.first().map(|node| node.index()));
+  report.unreachable_rooms = node_idxs.iter().filter(|idx| !reachable.contains(idx)).cloned().collect();
+
+  let mut edges: Vec<(usize, usize)> = map_graph
+    .graph
+    .edge_indices()
+    .filter_map(|edge| map_graph.graph.edge_endpoints(edge))
+    .map(|(a, b)| (a.index(), b.index()))
+    .filter(|(a, b)| !is_connected(map, *a, *b))
+    .collect();
+  edges.sort();
+  edges.dedup();
+  report.unrealised_edges = edges;
+
+  report
+}
+
+/// Place each room on an empty map in turn, with the same checks the crawler places rooms with
+fn check_placement(map: &Map, node_idxs: &[usize], report: &mut ValidationReport) {
+  let mut placed = Map::new(map.width, map.height);
+
+  for node_idx in node_idxs {
+    let room = &map.rooms[node_idx];
+
+    match super::room_conflict(&placed, room, 0) {
+      Some(Conflict::Overlap(idx)) => {
+        let other = placed.room_at(idx as i32 % map.width, idx as i32 / map.width).unwrap_or(*node_idx);
+        report.overlapping_rooms.push((other, *node_idx));
+      }
+      Some(_) => {
+        report.rooms_out_of_bounds.push(*node_idx);
+        continue;
+      }
+      None => {}
+    }
+
+    super::add_or_update_room(&mut placed, *node_idx, room.clone());
+  }
+}
+
+fn check_doors(map: &Map, node_idxs: &[usize], report: &mut ValidationReport) {
+  for node_idx in node_idxs {
+    let room = &map.rooms[node_idx];
+    let doors = room.doors_world();
+
+    for door in doors.iter() {
+      let dangling = DanglingDoor {
+        room: *node_idx,
+        x: door.x,
+        y: door.y,
+      };
+
+      let target = match map.rooms.get(&door.target) {
+        Some(target) => target,
+        None => {
+          report.dangling_doors.push(dangling);
+          continue;
+        }
+      };
+
+      let back_doors: Vec<_> = target.doors_world().into_iter().filter(|back| back.target == *node_idx).collect();
+      if back_doors.is_empty() {
+        report.one_sided_connections.push((*node_idx, door.target));
+        continue;
+      }
+
+      let (facing_type, facing_xy) = door.facing();
+      if !back_doors
+        .iter()
+        .any(|back| back.door_type == facing_type && (back.x, back.y) == facing_xy)
+      {
+        report.dangling_doors.push(dangling);
+      }
+    }
+
+    // Used and secret door tiles must belong to a connection
+    for xy in door_tiles(room) {
+      if !doors.iter().any(|door| (door.x, door.y) == xy) {
+        report.dangling_doors.push(DanglingDoor {
+          room: *node_idx,
+          x: xy.0,
+          y: xy.1,
+        });
+      }
+    }
+  }
+}
+
+/// Map tile coordinates of a room's used and secret door tiles
+fn door_tiles(room: &Room) -> Vec<(i32, i32)> {
+  let bounds = room.bounds();
+  room
+    .tiles
+    .iter()
+    .enumerate()
+    .filter(|(_, tile)| matches!(tile, 6 | 7))
+    .map(|(idx, _)| (bounds.x + idx as i32 % bounds.w, bounds.y + idx as i32 / bounds.w))
+    .collect()
+}
+
+fn is_connected(map: &Map, node_a: usize, node_b: usize) -> bool {
+  map
+    .rooms
+    .get(&node_a)
+    .is_some_and(|room| room.doors_world().iter().any(|door| door.target == node_b))
+}
+
+fn reachable_rooms(map: &Map, start: Option<usize>) -> HashSet<usize> {
+  let mut reached = HashSet::new();
+  let mut queue = VecDeque::new();
+
+  if let Some(start) = start.filter(|start| map.rooms.contains_key(start)) {
+    reached.insert(start);
+    queue.push_back(start);
+  }
+
+  while let Some(node_idx) = queue.pop_front() {
+    for door in map.rooms[&node_idx].doors_world() {
+      if map.rooms.contains_key(&door.target) && reached.insert(door.target) {
+        queue.push_back(door.target);
+      }
+    }
+  }
+
+  reached
+}
@@ -0,0 +1,306 @@
+use crate::{
+  common::xy_idx,
+  map::{room::Room, Map},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Where and how coarse the minimap is exported
+#[derive(Clone)]
+pub struct MinimapConfig {
+  /// Width and height of a cell, in tiles
+  pub cell_size: i32,
+  pub json_path: Option<PathBuf>,
+  pub png_path: Option<PathBuf>,
+  /// Width and height of a cell in the PNG, in pixels
+  pub png_scale: u32,
+}
+
+impl Default for MinimapConfig {
+  fn default() -> MinimapConfig {
+    MinimapConfig {
+      cell_size: 4,
+      json_path: Some(PathBuf::from("target/minimap.json")),
+      png_path: Some(PathBuf::from("target/minimap.png")),
+      png_scale: 8,
+    }
+  }
+}
+
+/// A flag for each side of a cell
+#[derive(Clone, Copy, Default, Serialize, Debug)]
+pub struct Edges {
+  pub north: bool,
+  pub east: bool,
+  pub south: bool,
+  pub west: bool,
+}
+
+impl Edges {
+  fn set(&mut self, door_type: u8) {
+    match door_type {
+      2 => self.north = true,
+      3 => self.east = true,
+      4 => self.south = true,
+      5 => self.west = true,
+      _ => {}
+    }
+  }
+}
+
+#[derive(Clone, Default, Serialize, Debug)]
+pub struct Cell {
+  /// Node index of the room covering most of the cell
+  pub room: Option<usize>,
+  /// Sides that border another room or empty space
+  pub walls: Edges,
+  /// Sides with a door through them
+  pub doors: Edges,
+  /// Set by the game as the player explores
+  pub visited: bool,
+  /// Part of a secret room, or has a secret door
+  pub secret: bool,
+}
+
+/// The placed rooms projected onto a coarse grid of cells, for an in game world map
+#[derive(Clone, Serialize, Debug)]
+pub struct Minimap {
+  pub cell_size: i32,
+  /// Width and height in cells
+  pub width: i32,
+  pub height: i32,
+  pub cells: Vec<Cell>,
+}
+
+impl Minimap {
+  pub fn new(map: &Map, cell_size: i32) -> Minimap {
+    let cell_size = cell_size.max(1);
+    let width = (map.width + cell_size - 1) / cell_size;
+    let height = (map.height + cell_size - 1) / cell_size;
+
+    let mut minimap = Minimap {
+      cell_size,
+      width,
+      height,
+      cells: vec![Cell::default(); (width * height) as usize],
+    };
+
+    for y in 0..height {
+      for x in 0..width {
+        let room = minimap.majority_room(map, x, y);
+        let cell = minimap.cell_mut(x, y).unwrap();
+        cell.room = room;
+        cell.secret = room.is_some_and(|idx| is_secret_room(&map.rooms[&idx]));
+      }
+    }
+
+    for y in 0..height {
+      for x in 0..width {
+        let room = minimap.cells[xy_idx(x, y, width)].room;
+        if room.is_none() {
+          continue;
+        }
+
+        let mut walls = Edges::default();
+        for (door_type, (dx, dy)) in [(2, (0, -1)), (3, (1, 0)), (4, (0, 1)), (5, (-1, 0))] {
+          if minimap.cell(x + dx, y + dy).and_then(|cell| cell.room) != room {
+            walls.set(door_type);
+          }
+        }
+        minimap.cell_mut(x, y).unwrap().walls = walls;
+      }
+    }
+
+    for door in map.rooms.values().flat_map(|room| room.doors_world()) {
+      if let Some(cell) = minimap.cell_mut(door.x / cell_size, door.y / cell_size) {
+        cell.doors.set(door.door_type);
+        cell.secret |= door.secret;
+      }
+    }
+
+    minimap
+  }
+
+  pub fn cell(&self, x: i32, y: i32) -> Option<&Cell> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
+    }
+    self.cells.get(xy_idx(x, y, self.width))
+  }
+
+  fn cell_mut(&mut self, x: i32, y: i32) -> Option<&mut Cell> {
+    if x < 0 || y < 0 || x >= self.width || y >= self.height {
+      return None;
+    }
+    self.cells.get_mut(xy_idx(x, y, self.width))
+  }
+
+  /// Mark every cell of a room as visited, as the player enters it
+  pub fn visit_room(&mut self, node_idx: usize) {
+    for cell in self.cells.iter_mut().filter(|cell| cell.room == Some(node_idx)) {
+      cell.visited = true;
+    }
+  }
+
+  /// The room covering the most tiles of a cell
+  fn majority_room(&self, map: &Map, cell_x: i32, cell_y: i32) -> Option<usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+
+    for y in cell_y * self.cell_size..((cell_y + 1) * self.cell_size).min(map.height) {
+      for x in cell_x * self.cell_size..((cell_x + 1) * self.cell_size).min(map.width) {
+        if let Some(room) = map.room_at(x, y) {
+          *counts.entry(room).or_insert(0) += 1;
+        }
+      }
+    }
+
+    // Ties go to the lower node index, so the result doesn't depend on hash order
+    counts
+      .into_iter()
+      .max_by_key(|(room, count)| (*count, usize::MAX - room))
+      .map(|(room, _)| room)
+  }
+
+  /// Draw the cells as text, two characters per cell with walls between them. Doors are gaps in the walls, secret
+  /// cells are "??" and visited cells "##"
+  pub fn to_ascii(&self) -> String {
+    // Each edge is shared by two cells, it is a wall if either side has one, unless either side has a door
+    let is_north_wall = |x: i32, y: i32| -> bool {
+      let (upper, lower) = (self.cell(x, y - 1), self.cell(x, y));
+      let door = upper.is_some_and(|c| c.doors.south) || lower.is_some_and(|c| c.doors.north);
+      let wall = upper.is_some_and(|c| c.walls.south) || lower.is_some_and(|c| c.walls.north);
+      wall && !door
+    };
+    let is_west_wall = |x: i32, y: i32| -> bool {
+      let (left, right) = (self.cell(x - 1, y), self.cell(x, y));
+      let door = left.is_some_and(|c| c.doors.east) || right.is_some_and(|c| c.doors.west);
+      let wall = left.is_some_and(|c| c.walls.east) || right.is_some_and(|c| c.walls.west);
+      wall && !door
+    };
+    let corner = |x: i32, y: i32| -> char {
+      let touches_wall = is_north_wall(x - 1, y) || is_north_wall(x, y) || is_west_wall(x, y - 1) || is_west_wall(x, y);
+      if touches_wall {
+        '+'
+      } else {
+        ' '
+      }
+    };
+
+    // Crop to the cells with rooms, the same as debug::print_map leaves out empty rows
+    let used: Vec<(i32, i32)> = (0..self.height)
+      .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+      .filter(|(x, y)| self.cell(*x, *y).unwrap().room.is_some())
+      .collect();
+    let (min_x, max_x) = (
+      used.iter().map(|c| c.0).min().unwrap_or(0),
+      used.iter().map(|c| c.0).max().unwrap_or(-1),
+    );
+    let (min_y, max_y) = (
+      used.iter().map(|c| c.1).min().unwrap_or(0),
+      used.iter().map(|c| c.1).max().unwrap_or(-1),
+    );
+
+    let mut ascii = String::new();
+
+    for y in min_y..=max_y + 1 {
+      for x in min_x..=max_x + 1 {
+        ascii.push(corner(x, y));
+        if x <= max_x {
+          ascii.push_str(if is_north_wall(x, y) { "--" } else { "  " });
+        }
+      }
+      ascii.push('\n');
+
+      if y > max_y {
+        break;
+      }
+
+      for x in min_x..=max_x + 1 {
+        ascii.push(if is_west_wall(x, y) { '|' } else { ' ' });
+        match self.cell(x, y) {
+          _ if x > max_x => {}
+          Some(Cell { room: None, .. }) | None => ascii.push_str("  "),
+          Some(Cell { secret: true, .. }) => ascii.push_str("??"),
+          Some(Cell { visited: true, .. }) => ascii.push_str("##"),
+          Some(_) => ascii.push_str(".."),
+        }
+      }
+      ascii.push('\n');
+    }
+
+    ascii
+  }
+
+  pub fn to_json(&self) -> serde_json::Result<String> {
+    serde_json::to_string(self)
+  }
+
+  pub fn save_json(&self, path: &Path) -> io::Result<()> {
+    create_parent_dir(path)?;
+    fs::write(path, self.to_json()?)
+  }
+
+  /// Render each cell as a `scale` pixel square, with walls drawn along its edges and gaps left for doors
+  pub fn save_png(&self, path: &Path, scale: u32) -> io::Result<()> {
+    let scale = scale.max(3);
+    let (width, height) = (self.width as u32 * scale, self.height as u32 * scale);
+    let mut pixels = vec![0u8; (width * height * 3) as usize];
+
+    const ROOM: [u8; 3] = [60, 90, 160];
+    const VISITED: [u8; 3] = [90, 160, 90];
+    const SECRET: [u8; 3] = [150, 70, 150];
+    const WALL: [u8; 3] = [230, 230, 230];
+
+    for cy in 0..self.height {
+      for cx in 0..self.width {
+        let cell = self.cell(cx, cy).unwrap();
+        if cell.room.is_none() {
+          continue;
+        }
+
+        let fill = match cell {
+          Cell { secret: true, .. } => SECRET,
+          Cell { visited: true, .. } => VISITED,
+          _ => ROOM,
+        };
+
+        for py in 0..scale {
+          for px in 0..scale {
+            let (last, middle) = (scale - 1, (scale / 3)..(scale - scale / 3));
+            let edge = |on_edge: bool, wall: bool, door: bool, along: u32| on_edge && wall && !(door && middle.contains(&along));
+
+            let is_wall = edge(py == 0, cell.walls.north, cell.doors.north, px)
+              || edge(px == last, cell.walls.east, cell.doors.east, py)
+              || edge(py == last, cell.walls.south, cell.doors.south, px)
+              || edge(px == 0, cell.walls.west, cell.doors.west, py);
+
+            let idx = (((cy as u32 * scale + py) * width + cx as u32 * scale + px) * 3) as usize;
+            pixels[idx..idx + 3].copy_from_slice(if is_wall { &WALL } else { &fill });
+          }
+        }
+      }
+    }
+
+    create_parent_dir(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&pixels).map_err(io::Error::other)
+  }
+}
+
+fn is_secret_room(room: &Room) -> bool {
+  room.template.tags.iter().any(|tag| tag == "secret")
+}
+
+fn create_parent_dir(path: &Path) -> io::Result<()> {
+  match path.parent() {
+    Some(dir) => fs::create_dir_all(dir),
+    None => Ok(()),
+  }
+}
@@ -0,0 +1,147 @@
+use super::{
+  common::xy_idx,
+  graph::MapGraph,
+  map::{layers, room::RoomType, Map},
+};
+use petgraph::stable_graph::NodeIndex;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnKind {
+  Enemy = 1,
+  Item = 2,
+  Boss = 3,
+}
+
+/// A spawn point inside a room, in map tile coordinates
+#[derive(Clone, Serialize, Debug)]
+pub struct Spawn {
+  /// Node index of the room
+  pub room: usize,
+  pub x: i32,
+  pub y: i32,
+  pub kind: SpawnKind,
+  /// Difficulty or rarity, from 1 up to SpawnTable::tiers
+  pub tier: u32,
+}
+
+/// How many spawns each room gets and what they are
+#[derive(Clone)]
+pub struct SpawnTable {
+  /// Relative chance of each kind of spawn
+  pub kinds: Vec<(SpawnKind, u32)>,
+  /// Spawns per standable tile in the lightest room
+  pub min_density: f64,
+  /// Spawns per standable tile in the heaviest room
+  pub max_density: f64,
+  pub tiers: u32,
+  /// Tiles around each used door kept free of spawns
+  pub door_clearance: i32,
+  /// Rooms with any of these tags never get spawns (eg: save, shop)
+  pub safe_tags: Vec<String>,
+}
+
+impl Default for SpawnTable {
+  fn default() -> SpawnTable {
+    SpawnTable {
+      kinds: vec![(SpawnKind::Enemy, 4), (SpawnKind::Item, 1)],
+      min_density: 0.1,
+      max_density: 0.35,
+      tiers: 3,
+      door_clearance: 1,
+      safe_tags: vec!["save".to_string(), "shop".to_string()],
+    }
+  }
+}
+
+/// Place spawn points in every room, scaling density and tier with the node's weight. The start room and safe rooms
+/// are left empty, and the boss room gets the boss. Spawns are marked on the map's entity layer. A table without any
+/// tiers places nothing
+pub fn spawn(map: &mut Map, map_graph: &MapGraph, table: &SpawnTable, seed: u64) {
+  if table.tiers == 0 {
+    return;
+  }
+
+  let max_weight = map_graph.graph.node_weights().max().cloned().unwrap_or(1).max(1);
+  let mut node_idxs: Vec<usize> = map.rooms.keys().cloned().collect();
+  node_idxs.sort();
+
+  for node_idx in node_idxs {
+    let room = map.rooms.get(&node_idx).unwrap();
+    let mut rng = StdRng::seed_from_u64(seed.rotate_left(48) ^ node_idx as u64);
+
+    let is_safe = room.template.tags.iter().any(|tag| table.safe_tags.contains(tag));
+    if room.template.room_type == RoomType::Start || is_safe {
+      continue;
+    }
+
+    let weight = *map_graph.graph.node_weight(NodeIndex::new(node_idx)).unwrap_or(&0);
+    let difficulty = weight as f64 / max_weight as f64;
+
+    let mut tiles = standable_tiles(map, node_idx, table.door_clearance);
+    tiles.shuffle(&mut rng);
+
+    let mut spawns: Vec<(i32, i32, SpawnKind)> = vec![];
+
+    if room.template.room_type == RoomType::Boss {
+      if let Some((x, y)) = tiles.pop() {
+        spawns.push((x, y, SpawnKind::Boss));
+      }
+    }
+
+    let density = table.min_density + (table.max_density - table.min_density) * difficulty;
+    let count = (tiles.len() as f64 * density).round() as usize;
+
+    for (x, y) in tiles.into_iter().take(count) {
+      if let Some(kind) = random_kind(&table.kinds, &mut rng) {
+        spawns.push((x, y, kind));
+      }
+    }
+
+    for (x, y, kind) in spawns {
+      // Heavier nodes lean towards higher tiers, with a little variation
+      let tier_roll = difficulty * (table.tiers - 1) as f64 + rng.gen_range(-0.5..0.5);
+      let tier = (tier_roll.round().max(0.0) as u32 + 1).min(table.tiers);
+
+      map.layers.entities[xy_idx(x, y, map.width)] = kind as u8;
+      map.spawns.push(Spawn {
+        room: node_idx,
+        x,
+        y,
+        kind,
+        tier,
+      });
+    }
+  }
+}
+
+fn random_kind(kinds: &[(SpawnKind, u32)], rng: &mut StdRng) -> Option<SpawnKind> {
+  kinds.choose_weighted(rng, |(_, weight)| *weight).ok().map(|(kind, _)| *kind)
+}
+
+/// Empty interior tiles of a room with something to stand on, away from its used doors
+fn standable_tiles(map: &Map, node_idx: usize, door_clearance: i32) -> Vec<(i32, i32)> {
+  let room = map.rooms.get(&node_idx).unwrap();
+  let doors = room.doors_world();
+
+  room
+    .interior_tiles()
+    .into_iter()
+    .filter(|(x, y)| {
+      let idx = xy_idx(*x, *y, map.width);
+      let below = xy_idx(*x, y + 1, map.width);
+      let is_empty = map.layers.collision[idx] == layers::EMPTY && map.layers.decoration[idx] == 0;
+      let has_floor = matches!(
+        map.layers.collision.get(below),
+        Some(&layers::SOLID) | Some(&layers::ONE_WAY_PLATFORM)
+      );
+      let near_door = doors
+        .iter()
+        .any(|door| (door.x - x).abs() <= door_clearance && (door.y - y).abs() <= door_clearance);
+
+      is_empty && has_floor && !near_door && map.room_at(*x, *y) == Some(node_idx)
+    })
+    .collect()
+}
@@ -0,0 +1,92 @@
+use crate::map::{
+  combination_cache::{self, CACHE_VERSION},
+  room_templates::{CombinationTable, RoomTemplate, RoomTemplateMap, RoomTemplates},
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// A cache file of the test's own, so tests running in parallel don't share one
+fn cache_path(name: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!("erebos-{}-{}.json", name, std::process::id()));
+  let _ = fs::remove_file(&path);
+  path
+}
+
+fn templates() -> RoomTemplateMap {
+  HashMap::from([(
+    0,
+    RoomTemplate {
+      w: 3,
+      h: 3,
+      tiles: vec![1, 2, 1, 5, 8, 3, 1, 4, 1],
+      ..Default::default()
+    },
+  )])
+}
+
+fn combinations() -> CombinationTable {
+  HashMap::from([(0, HashMap::from([(0, vec![((0, -2), 2, (2, 1), 4, (2, 3))])]))])
+}
+
+#[test]
+fn saved_combinations_load_back() {
+  let path = cache_path("round-trip");
+  let hash = combination_cache::template_hash(&templates());
+
+  combination_cache::save(&path, hash, &combinations()).unwrap();
+  assert_eq!(combination_cache::load(&path, hash), Some(combinations()));
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn changed_templates_invalidate_the_cache() {
+  let path = cache_path("stale-hash");
+  let mut templates = templates();
+  let hash = combination_cache::template_hash(&templates);
+  combination_cache::save(&path, hash, &combinations()).unwrap();
+
+  // Moving the west door makes a different template set
+  templates.get_mut(&0).unwrap().tiles = vec![1, 2, 1, 1, 8, 3, 5, 4, 1];
+  let changed = combination_cache::template_hash(&templates);
+  assert_ne!(changed, hash);
+  assert_eq!(combination_cache::load(&path, changed), None);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn other_cache_versions_are_ignored() {
+  let path = cache_path("version");
+  let hash = combination_cache::template_hash(&templates());
+  combination_cache::save(&path, hash, &combinations()).unwrap();
+
+  let contents = fs::read_to_string(&path).unwrap();
+  let version = format!("\"version\":{}", CACHE_VERSION);
+  assert!(contents.contains(&version));
+  fs::write(&path, contents.replace(&version, &format!("\"version\":{}", CACHE_VERSION + 1))).unwrap();
+
+  assert_eq!(combination_cache::load(&path, hash), None);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn corrupt_caches_are_recalculated() {
+  let path = cache_path("corrupt");
+  fs::write(&path, "{\"version\": 1, \"hash\": ").unwrap();
+
+  let loaded = RoomTemplates::load_or_calculate(&path);
+  let calculated = RoomTemplates::new();
+  assert_eq!(loaded.rooms.len(), calculated.rooms.len());
+  for (idx, template) in calculated.rooms.iter() {
+    assert_eq!(
+      loaded.rooms[idx].valid_combinations, template.valid_combinations,
+      "template {}",
+      idx
+    );
+  }
+
+  // ..and the cache is rewritten for next time
+  let hash = combination_cache::template_hash(&calculated.rooms);
+  assert!(combination_cache::load(&path, hash).is_some());
+  fs::remove_file(&path).unwrap();
+}
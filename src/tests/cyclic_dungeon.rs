@@ -0,0 +1,83 @@
+use super::config;
+use crate::{
+  generate_map,
+  graph::{self, cyclic_dungeon_graph, MapGraph},
+  load_templates,
+  map::{room::RoomType, Map},
+};
+use petgraph::stable_graph::StableGraph;
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::HashSet;
+
+fn dungeon(sub_cycles: usize, seed: u64) -> MapGraph {
+  let (graph, roles) = cyclic_dungeon_graph::new(sub_cycles, &mut StdRng::seed_from_u64(seed));
+  let mut map_graph = MapGraph::new(graph);
+  map_graph.roles = roles;
+  map_graph
+}
+
+fn boss_rooms(map: &Map) -> Vec<usize> {
+  let mut bosses: Vec<usize> = map
+    .rooms
+    .iter()
+    .filter(|(_, room)| room.template.room_type == RoomType::Boss)
+    .map(|(idx, _)| *idx)
+    .collect();
+  bosses.sort();
+  bosses
+}
+
+#[test]
+fn boss_is_the_heaviest_node() {
+  let mut graph = StableGraph::<usize, usize>::default();
+  let nodes = [3, 7, 2, 7, 1].iter().map(|weight| graph.add_node(*weight)).collect();
+  let map_graph = MapGraph::new((graph, nodes));
+
+  // Tied for heaviest, the later node wins
+  assert_eq!(graph::boss_node(&map_graph).map(|node| node.index()), Some(3));
+  assert_eq!(graph::boss_node(&MapGraph::new((StableGraph::default(), vec![]))), None);
+}
+
+#[test]
+fn goal_becomes_the_boss_room() {
+  let mut templates = load_templates(&config(0));
+  let mut placed = 0;
+
+  for seed in 0..8 {
+    let mut map_graph = dungeon(2, seed);
+    assert_eq!(graph::boss_node(&map_graph).map(|node| node.index()), Some(1));
+
+    let map = generate_map(&mut map_graph, &mut templates, config(seed));
+    let bosses = boss_rooms(&map);
+    assert!(bosses.is_empty() || bosses == [1], "seed {}: boss rooms {:?}", seed, bosses);
+    placed += bosses.len();
+  }
+
+  assert!(placed >= 6, "the goal was placed in {} of 8 maps", placed);
+}
+
+#[test]
+fn role_nodes_get_matching_rooms() {
+  let mut templates = load_templates(&config(0));
+  let mut matched: HashSet<String> = HashSet::new();
+
+  for seed in 0..8 {
+    let mut map_graph = dungeon(3, seed);
+    let map = generate_map(&mut map_graph, &mut templates, config(seed));
+
+    // Rooms whose template has the tag their node's role asked for first
+    let tags: Vec<&String> = map_graph
+      .roles
+      .iter()
+      .filter_map(|(idx, role)| Some((map.rooms.get(idx)?, role.tags.first()?)))
+      .filter(|(room, tag)| room.template.tags.contains(tag))
+      .map(|(_, tag)| tag)
+      .collect();
+    assert!(!tags.is_empty(), "seed {}: no role was given a matching room", seed);
+    matched.extend(tags.into_iter().cloned());
+  }
+
+  for tag in ["lock", "key", "shortcut", "arena", "treasure"] {
+    assert!(matched.contains(tag), "no {} room placed", tag);
+  }
+}
@@ -0,0 +1,63 @@
+use crate::crawler;
+use crate::graph::{directed_star_graph, MapGraph};
+use crate::map::{room::Room, room_template_definitions::calculate_doors_xy, room_templates::RoomTemplate, Map};
+
+/// Three doors in a run along the north face, and one on each other face
+const TILES: [u8; 25] = [
+  1, 2, 2, 2, 1, //
+  5, 8, 8, 8, 3, //
+  1, 8, 8, 8, 1, //
+  1, 8, 8, 8, 1, //
+  1, 4, 1, 1, 1,
+];
+
+fn room(max_doors: Option<u32>) -> Room {
+  Room::new(RoomTemplate {
+    w: 5,
+    h: 5,
+    tiles: TILES.to_vec(),
+    possible_doors_xy: calculate_doors_xy(&TILES.to_vec(), 5),
+    max_doors,
+    ..Default::default()
+  })
+}
+
+#[test]
+fn door_slots_skip_used_doors_and_their_neighbours() {
+  let room = room(None);
+  // Every other tile of the north run, and one door on each other face
+  assert_eq!(room.template.door_capacity(), 5);
+
+  // The middle of the run blocks both of its neighbours
+  assert_eq!(room.free_door_slots_after((3, 1)), 3);
+  // The end of the run leaves the far end free
+  assert_eq!(room.free_door_slots_after((2, 1)), 4);
+  assert_eq!(room.free_door_slots_after((1, 2)), 4);
+}
+
+#[test]
+fn door_slots_stop_at_max_doors() {
+  let mut room = room(Some(3));
+  assert_eq!(room.template.door_capacity(), 3);
+  assert_eq!(room.free_door_slots_after((1, 2)), 2);
+
+  room.add_door(0, 1, 5, (1, 2), true);
+  assert_eq!(room.free_door_slots_after((5, 2)), 1);
+}
+
+#[test]
+fn placement_reserves_doors_for_pending_neighbours() {
+  // Node 0 is the hub of three spokes, and is connecting to node 1 through its west door
+  let star = MapGraph::new(directed_star_graph::new(4, false, false));
+  let mut map = Map::new(40, 40);
+  let reserves = |room: &Room, map: &Map| crawler::reserves_doors(room, 0, 1, (1, 2), map, &star.neighbour_map);
+
+  assert!(reserves(&room(None), &map));
+  // One door left after the west door, for two more spokes
+  assert!(!reserves(&room(Some(2)), &map));
+
+  // Spokes already on the map don't need a door kept for them
+  map.rooms.insert(2, room(None));
+  map.rooms.insert(3, room(None));
+  assert!(reserves(&room(Some(1)), &map));
+}
@@ -0,0 +1,464 @@
+use crate::graph::{
+  barbell_graph, cyclic_dungeon_graph, directed_binomial_tree_graph, directed_cycle_graph, directed_gnp_random_graph, directed_grid_graph,
+  directed_heavy_hex_graph, directed_heavy_square_graph, directed_hexagonal_lattice_graph, directed_path_graph, directed_star_graph,
+  directed_wheel_graph, generalized_petersen_graph, lollipop_graph, random_spanning_tree_graph, random_tree_graph, GraphResult,
+};
+use petgraph::{stable_graph::NodeIndex, Direction};
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Node count, edge count and how many nodes have each (undirected) degree
+struct Snapshot {
+  nodes: usize,
+  edges: usize,
+  degrees: BTreeMap<usize, usize>,
+}
+
+fn snapshot((graph, nodes): &GraphResult) -> Snapshot {
+  assert_eq!(nodes.len(), graph.node_count(), "every node is listed once");
+  assert_eq!(nodes.iter().collect::<HashSet<_>>().len(), nodes.len(), "every node is listed once");
+
+  let mut degrees = BTreeMap::new();
+  for node in graph.node_indices() {
+    *degrees.entry(graph.neighbors_undirected(node).count()).or_insert(0) += 1;
+  }
+
+  Snapshot {
+    nodes: graph.node_count(),
+    edges: graph.edge_count(),
+    degrees,
+  }
+}
+
+/// Degree counts, adding up repeated degrees and leaving out empty ones
+fn degrees(counts: &[(usize, usize)]) -> BTreeMap<usize, usize> {
+  let mut degrees = BTreeMap::new();
+  for (degree, count) in counts.iter().filter(|(_, count)| *count > 0) {
+    *degrees.entry(*degree).or_insert(0) += count;
+  }
+  degrees
+}
+
+#[test]
+fn barbell() {
+  // Two complete graphs K_m joined through a path of p nodes
+  for (m, p) in [(3, 0), (3, 1), (4, 0), (5, 4), (9, 9)] {
+    let graph = snapshot(&barbell_graph::new(m, p));
+
+    assert_eq!(graph.nodes, 2 * m + p, "barbell({}, {})", m, p);
+    assert_eq!(graph.edges, m * (m - 1) + p + 1, "barbell({}, {})", m, p);
+    // The two mesh nodes joined to the path gain an edge
+    assert_eq!(
+      graph.degrees,
+      degrees(&[(m - 1, 2 * m - 2), (m, 2), (2, p)]),
+      "barbell({}, {})",
+      m,
+      p
+    );
+  }
+}
+
+#[test]
+fn binomial_tree() {
+  // B_n is two copies of B_n-1 with an edge between their roots
+  let mut expected = degrees(&[(0, 1)]);
+
+  for order in 0..8 {
+    for bidirectional in [false, true] {
+      let graph = snapshot(&directed_binomial_tree_graph::new(order, bidirectional));
+      let directions = if bidirectional { 2 } else { 1 };
+
+      assert_eq!(graph.nodes, 1 << order, "binomial_tree({})", order);
+      assert_eq!(graph.edges, ((1 << order) - 1) * directions, "binomial_tree({})", order);
+
+      let expected_degrees: BTreeMap<usize, usize> = expected.iter().map(|(degree, count)| (degree * directions, *count)).collect();
+      assert_eq!(graph.degrees, expected_degrees, "binomial_tree({})", order);
+    }
+
+    let mut next = BTreeMap::new();
+    for (degree, count) in expected.iter() {
+      *next.entry(*degree).or_insert(0) += count * 2;
+    }
+    // Both roots gain an edge. The roots have the highest degree, order
+    *next.entry(order as usize).or_insert(0) -= 2;
+    *next.entry(order as usize + 1).or_insert(0) += 2;
+    expected = next.into_iter().filter(|(_, count)| *count > 0).collect();
+  }
+}
+
+#[test]
+fn gnp_random_graph_extremes() {
+  let mut rng = StdRng::seed_from_u64(0);
+
+  let empty = snapshot(&directed_gnp_random_graph::new(12, 0.0, &mut rng));
+  assert_eq!((empty.nodes, empty.edges), (12, 0));
+
+  // Every ordered pair of distinct nodes
+  let complete = snapshot(&directed_gnp_random_graph::new(12, 1.0, &mut rng));
+  assert_eq!((complete.nodes, complete.edges), (12, 12 * 11));
+  assert_eq!(complete.degrees, degrees(&[(22, 12)]));
+}
+
+#[test]
+fn gnp_random_graph_density() {
+  let (n, p) = (30, 0.3);
+  let runs = 40;
+  let mut total_edges = 0;
+
+  for seed in 0..runs {
+    let (graph, _) = directed_gnp_random_graph::new(n, p, &mut StdRng::seed_from_u64(seed));
+
+    let edges: Vec<_> = graph.edge_indices().filter_map(|edge| graph.edge_endpoints(edge)).collect();
+    assert!(edges.iter().all(|(a, b)| a != b), "no self loops");
+    assert_eq!(edges.iter().collect::<HashSet<_>>().len(), edges.len(), "no parallel edges");
+
+    total_edges += edges.len();
+  }
+
+  // Each of the n(n - 1) possible edges is made with probability p
+  let expected = p * (n * (n - 1)) as f64;
+  let mean = total_edges as f64 / runs as f64;
+  assert!(
+    (mean - expected).abs() < expected * 0.05,
+    "mean of {} edges, expected {}",
+    mean,
+    expected
+  );
+}
+
+#[test]
+fn heavy_hex() {
+  for d in [3, 5, 7, 9] {
+    for bidirectional in [false, true] {
+      let graph = snapshot(&directed_heavy_hex_graph::new(d, bidirectional));
+      let directions = if bidirectional { 2 } else { 1 };
+      let nodes = (5 * d * d - 2 * d - 1) / 2;
+
+      // A planar graph of (d - 1)^2 / 2 heavy hexagons, two syndrome nodes on the boundary hang off a single flag
+      assert_eq!(graph.nodes, nodes, "heavy_hex({})", d);
+      assert_eq!(graph.edges, (3 * d * d - 2 * d - 1) * directions, "heavy_hex({})", d);
+      assert_eq!(
+        graph.degrees,
+        degrees(&[
+          (directions, 2),
+          (2 * directions, nodes - (d - 1) * (d - 1) - 2),
+          (3 * directions, (d - 1) * (d - 1))
+        ]),
+        "heavy_hex({})",
+        d
+      );
+    }
+  }
+}
+
+#[test]
+fn heavy_square() {
+  for d in [3, 5, 7] {
+    for bidirectional in [false, true] {
+      let graph = snapshot(&directed_heavy_square_graph::new(d, bidirectional));
+      let directions = if bidirectional { 2 } else { 1 };
+      let nodes = 3 * d * d - 2 * d;
+
+      // A planar graph of (d - 1)^2 heavy squares
+      assert_eq!(graph.nodes, nodes, "heavy_square({})", d);
+      assert_eq!(graph.edges, (4 * d * d - 4 * d) * directions, "heavy_square({})", d);
+      assert_eq!(
+        graph.edges / directions - graph.nodes + 1,
+        (d - 1) * (d - 1),
+        "heavy_square({}) faces",
+        d
+      );
+    }
+  }
+
+  // Golden degree sequences
+  assert_eq!(
+    snapshot(&directed_heavy_square_graph::new(3, false)).degrees,
+    degrees(&[(1, 2), (2, 13), (3, 4), (4, 2)])
+  );
+  assert_eq!(
+    snapshot(&directed_heavy_square_graph::new(5, false)).degrees,
+    degrees(&[(1, 2), (2, 43), (3, 8), (4, 12)])
+  );
+}
+
+#[test]
+fn hexagonal_lattice() {
+  for (rows, cols) in [(1, 1), (1, 2), (2, 2), (4, 5), (5, 4)] {
+    for bidirectional in [false, true] {
+      let graph = snapshot(&directed_hexagonal_lattice_graph::new(rows, cols, bidirectional));
+      let directions = if bidirectional { 2 } else { 1 };
+      let nodes = 2 * (rows + 1) * (cols + 1) - 2;
+      let edges = 3 * rows * cols + 2 * rows + 2 * cols - 1;
+
+      assert_eq!(graph.nodes, nodes, "hexagonal_lattice({}, {})", rows, cols);
+      assert_eq!(graph.edges, edges * directions, "hexagonal_lattice({}, {})", rows, cols);
+      // Every node has degree 2 or 3
+      let degree_3 = 2 * edges - 2 * nodes;
+      assert_eq!(
+        graph.degrees,
+        degrees(&[(2 * directions, nodes - degree_3), (3 * directions, degree_3)]),
+        "hexagonal_lattice({}, {})",
+        rows,
+        cols
+      );
+    }
+  }
+}
+
+#[test]
+fn generalized_petersen() {
+  // G(n, k) is 3-regular with 2n nodes and 3n edges
+  for (n, k) in [(3, 1), (4, 1), (5, 2), (7, 3), (11, 5)] {
+    let graph = snapshot(&generalized_petersen_graph::new(n, k));
+
+    assert_eq!(graph.nodes, 2 * n, "generalized_petersen({}, {})", n, k);
+    assert_eq!(graph.edges, 3 * n, "generalized_petersen({}, {})", n, k);
+    assert_eq!(graph.degrees, degrees(&[(3, 2 * n)]), "generalized_petersen({}, {})", n, k);
+  }
+}
+
+#[test]
+fn path() {
+  for n in [1, 2, 3, 10] {
+    let graph = snapshot(&directed_path_graph::new(n, false));
+
+    assert_eq!(graph.nodes, n, "path({})", n);
+    assert_eq!(graph.edges, n - 1, "path({})", n);
+    if n > 1 {
+      assert_eq!(graph.degrees, degrees(&[(1, 2), (2, n - 2)]), "path({})", n);
+    }
+  }
+
+  assert_eq!(directed_path_graph::new(5, true).0.edge_count(), 8);
+}
+
+#[test]
+fn cycle() {
+  for n in [3, 4, 10] {
+    let graph = snapshot(&directed_cycle_graph::new(n, false));
+
+    assert_eq!(graph.nodes, n, "cycle({})", n);
+    assert_eq!(graph.edges, n, "cycle({})", n);
+    assert_eq!(graph.degrees, degrees(&[(2, n)]), "cycle({})", n);
+  }
+
+  // Too small to close without a parallel edge
+  assert_eq!(directed_cycle_graph::new(2, false).0.edge_count(), 1);
+  assert_eq!(directed_cycle_graph::new(5, true).0.edge_count(), 10);
+}
+
+#[test]
+fn star() {
+  for n in [2, 4, 8] {
+    for (inward, bidirectional) in [(false, false), (true, false)] {
+      let (graph, nodes) = directed_star_graph::new(n, inward, bidirectional);
+      let hub_out = graph.neighbors(nodes[0]).count();
+      let graph = snapshot(&(graph, nodes));
+
+      assert_eq!(graph.nodes, n, "star({})", n);
+      assert_eq!(graph.edges, n - 1, "star({})", n);
+      assert_eq!(graph.degrees, degrees(&[(n - 1, 1), (1, n - 1)]), "star({})", n);
+      assert_eq!(hub_out, if inward { 0 } else { n - 1 }, "star({}) inward:{}", n, inward);
+    }
+  }
+
+  assert_eq!(directed_star_graph::new(5, true, true).0.edge_count(), 8);
+}
+
+#[test]
+fn wheel() {
+  for n in [4, 5, 9] {
+    let graph = snapshot(&directed_wheel_graph::new(n, false, false));
+
+    assert_eq!(graph.nodes, n, "wheel({})", n);
+    assert_eq!(graph.edges, 2 * (n - 1), "wheel({})", n);
+    assert_eq!(graph.degrees, degrees(&[(n - 1, 1), (3, n - 1)]), "wheel({})", n);
+  }
+
+  assert_eq!(directed_wheel_graph::new(6, false, true).0.edge_count(), 20);
+}
+
+#[test]
+fn lollipop() {
+  // A complete graph K_m with a path of p nodes hanging off its last node
+  for (m, p) in [(3, 1), (4, 3), (5, 6)] {
+    let graph = snapshot(&lollipop_graph::new(m, p));
+
+    assert_eq!(graph.nodes, m + p, "lollipop({}, {})", m, p);
+    assert_eq!(graph.edges, m * (m - 1) / 2 + p, "lollipop({}, {})", m, p);
+    assert_eq!(
+      graph.degrees,
+      degrees(&[(m - 1, m - 1), (m, 1), (2, p - 1), (1, 1)]),
+      "lollipop({}, {})",
+      m,
+      p
+    );
+  }
+
+  let mesh = snapshot(&lollipop_graph::new(4, 0));
+  assert_eq!((mesh.edges, mesh.degrees), (6, degrees(&[(3, 4)])));
+}
+
+#[test]
+fn grid() {
+  for (r, c) in [(3, 3), (3, 5), (4, 6)] {
+    let graph = snapshot(&directed_grid_graph::new(r, c, false));
+
+    assert_eq!(graph.nodes, r * c, "grid({}, {})", r, c);
+    assert_eq!(graph.edges, r * (c - 1) + c * (r - 1), "grid({}, {})", r, c);
+    assert_eq!(
+      graph.degrees,
+      degrees(&[(2, 4), (3, 2 * (r - 2) + 2 * (c - 2)), (4, (r - 2) * (c - 2))]),
+      "grid({}, {})",
+      r,
+      c
+    );
+  }
+
+  // A single row is a path
+  let row = snapshot(&directed_grid_graph::new(1, 5, false));
+  assert_eq!((row.edges, row.degrees), (4, degrees(&[(1, 2), (2, 3)])));
+  assert_eq!(directed_grid_graph::new(2, 2, true).0.edge_count(), 8);
+}
+
+/// Depth of every node below the first, following edges forwards
+fn depths((graph, nodes): &GraphResult) -> HashMap<usize, usize> {
+  let mut depths = HashMap::from([(nodes[0].index(), 0)]);
+  let mut queue = vec![nodes[0]];
+  while let Some(node) = queue.pop() {
+    for child in graph.neighbors(node) {
+      assert!(
+        depths.insert(child.index(), depths[&node.index()] + 1).is_none(),
+        "node {} has two parents",
+        child.index()
+      );
+      queue.push(child);
+    }
+  }
+  depths
+}
+
+#[test]
+fn random_tree() {
+  for seed in 0..40 {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tree = random_tree_graph::new(25, 25, 1..=3, 0.4, &mut rng);
+    let (graph, nodes) = &tree;
+
+    assert_eq!(nodes.len(), 25, "seed {}", seed);
+    assert_eq!(graph.edge_count(), 24, "seed {}", seed);
+    assert_eq!(depths(&tree).len(), 25, "every node hangs off the root, seed {}", seed);
+    assert!(nodes.iter().all(|node| graph.neighbors(*node).count() <= 3), "seed {}", seed);
+
+    let tree = random_tree_graph::new(25, 3, 1..=3, 0.4, &mut rng);
+    assert!(depths(&tree).values().all(|depth| *depth <= 3), "seed {}", seed);
+    assert_eq!(tree.0.edge_count(), tree.1.len() - 1, "seed {}", seed);
+  }
+
+  // Full branching and no dead ends fill each level before the next
+  let tree = random_tree_graph::new(15, 10, 2..=2, 0.0, &mut StdRng::seed_from_u64(0));
+  let levels = depths(&tree).values().fold(BTreeMap::new(), |mut levels, depth| {
+    *levels.entry(*depth).or_insert(0) += 1;
+    levels
+  });
+  assert_eq!(levels, BTreeMap::from([(0, 1), (1, 2), (2, 4), (3, 8)]));
+
+  // The depth limit caps the size
+  let (graph, _) = random_tree_graph::new(100, 2, 1..=3, 0.0, &mut StdRng::seed_from_u64(0));
+  assert!(graph.node_count() <= 13);
+
+  // With every node a dead end, the tree still grows through the last node on the frontier, one parent per level
+  let tree = random_tree_graph::new(12, 20, 1..=2, 1.0, &mut StdRng::seed_from_u64(0));
+  assert_eq!(tree.1.len(), 12);
+  let mut parents_per_level: HashMap<usize, usize> = HashMap::new();
+  for (node, depth) in depths(&tree) {
+    if tree.0.neighbors_directed(NodeIndex::new(node), Direction::Outgoing).count() > 0 {
+      *parents_per_level.entry(depth).or_insert(0) += 1;
+    }
+  }
+  assert!(parents_per_level.values().all(|parents| *parents == 1));
+}
+
+#[test]
+fn random_spanning_tree() {
+  let (rows, cols) = (4, 5);
+  let (source, _) = directed_grid_graph::new(rows, cols, false);
+  let source_edges: HashSet<_> = source.edge_indices().filter_map(|edge| source.edge_endpoints(edge)).collect();
+  let cycle_rank = source_edges.len() - (rows * cols - 1);
+
+  for extra_edges in [0, 1, 3, cycle_rank, 100] {
+    for seed in 0..10 {
+      let mut rng = StdRng::seed_from_u64(seed);
+      let (graph, nodes) = random_spanning_tree_graph::new(directed_grid_graph::new(rows, cols, false), extra_edges, &mut rng);
+
+      assert_eq!(nodes.len(), rows * cols);
+      assert_eq!(
+        graph.edge_count(),
+        rows * cols - 1 + extra_edges.min(cycle_rank),
+        "extra_edges {}",
+        extra_edges
+      );
+      assert!(graph
+        .edge_indices()
+        .filter_map(|edge| graph.edge_endpoints(edge))
+        .all(|edge| source_edges.contains(&edge)));
+      assert!(graph.node_weights().eq(source.node_weights()), "weights are kept");
+
+      assert_eq!(reachable(&(graph, nodes.clone())), nodes.len(), "connected");
+    }
+  }
+
+  // Parallel edges and self-loops never make it through
+  let (mut doubled, nodes) = directed_cycle_graph::new(6, true);
+  doubled.add_edge(nodes[0], nodes[0], 0);
+  let (graph, _) = random_spanning_tree_graph::new((doubled, nodes), 10, &mut StdRng::seed_from_u64(0));
+  assert_eq!(graph.edge_count(), 6);
+}
+
+/// Nodes reachable from the first node, ignoring edge direction
+fn reachable((graph, nodes): &GraphResult) -> usize {
+  let mut seen = HashSet::from([nodes[0]]);
+  let mut queue = vec![nodes[0]];
+  while let Some(node) = queue.pop() {
+    queue.extend(graph.neighbors_undirected(node).filter(|next| seen.insert(*next)));
+  }
+  seen.len()
+}
+
+#[test]
+fn cyclic_dungeon() {
+  for sub_cycles in 0..5 {
+    for seed in 0..20 {
+      let mut rng = StdRng::seed_from_u64(seed);
+      let ((graph, nodes), roles) = cyclic_dungeon_graph::new(sub_cycles, &mut rng);
+      let context = format!("sub_cycles {} seed {}", sub_cycles, seed);
+
+      // Every inserted cycle adds one independent loop
+      assert_eq!(graph.edge_count(), nodes.len() + sub_cycles, "{}", context);
+      assert_eq!(reachable(&(graph.clone(), nodes.clone())), nodes.len(), "{}", context);
+      assert!(
+        nodes.iter().all(|node| graph.neighbors_undirected(*node).count() <= 4),
+        "{}",
+        context
+      );
+
+      // Every edge is part of a loop, so removing any one leaves the dungeon connected
+      for edge in graph.edge_indices() {
+        let mut cut = graph.clone();
+        cut.remove_edge(edge);
+        assert_eq!(reachable(&(cut, nodes.clone())), nodes.len(), "{} edge {:?}", context, edge);
+      }
+
+      // The start is first and the goal is heaviest, and neither needs a special room
+      let goal = nodes.iter().max_by_key(|node| graph.node_weight(**node)).unwrap();
+      assert_eq!(goal.index(), 1, "{}", context);
+      assert!(!roles.contains_key(&0) && !roles.contains_key(&1), "{}", context);
+      assert!(roles.keys().all(|node| *node < nodes.len()), "{}", context);
+
+      let count = |tag: &str| roles.values().filter(|role| role.tags == [tag]).count();
+      assert_eq!(count("lock"), count("key"), "every lock has a key, {}", context);
+      assert_eq!(count("arena"), count("treasure"), "every gambit has a reward, {}", context);
+    }
+  }
+}
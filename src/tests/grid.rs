@@ -0,0 +1,41 @@
+use crate::map::grid::{Grid, Stamp};
+
+const ROOM: [u8; 9] = [
+  1, 1, 1, //
+  1, 8, 1, //
+  1, 1, 1,
+];
+
+#[test]
+fn blit_clips_stamps_hanging_off_every_edge() {
+  for (x, y) in [(-2, 0), (2, 0), (0, -2), (0, 2), (-2, -2), (2, 2)] {
+    let mut grid = Grid::new(vec![0; 16], 4);
+    let stamp = Stamp::new(x, y, 3, &ROOM);
+
+    grid.blit(&stamp, |_, _, tile| tile);
+
+    assert!(!grid.contains(&stamp));
+    let written = grid.tiles().iter().filter(|tile| **tile != 0).count();
+    let on_grid = stamp.cells().filter(|(x, y, _)| (0..4).contains(x) && (0..4).contains(y)).count();
+    assert_eq!(written, on_grid);
+  }
+}
+
+#[test]
+fn blit_writes_the_last_row_and_column() {
+  let mut grid = Grid::new(vec![0; 9], 3);
+  grid.blit(&Stamp::new(0, 0, 3, &ROOM), |_, _, tile| tile);
+
+  assert_eq!(grid.tiles(), &ROOM);
+  assert_eq!(grid.get(2, 2), Some(1));
+  assert_eq!(grid.get(3, 2), None);
+}
+
+#[test]
+fn overlap_only_counts_non_empty_stamp_tiles() {
+  let grid = Grid::new(ROOM.to_vec(), 3);
+  let hollow = [0, 0, 0, 0, 1, 0, 0, 0, 0];
+
+  assert_eq!(grid.overlap(&Stamp::new(0, 0, 3, &hollow), |tile| tile == 8), Some(4));
+  assert_eq!(grid.overlap(&Stamp::new(1, 0, 3, &hollow), |tile| tile == 8), None);
+}
@@ -0,0 +1,70 @@
+use crate::{
+  common::xy_idx,
+  decoration::interior::{self, Detail, Details},
+  map::{
+    room::Room,
+    room_shapes::{self, RoomShape},
+    Map,
+  },
+  traversal::JumpPhysics,
+};
+
+/// A rectangle room away from the map's corner, with its lowest west door and highest east door connected
+fn room() -> Room {
+  let template = room_shapes::generate(RoomShape::Rectangle, 14, 10).unwrap();
+  let west = *template.possible_doors_xy[&5].iter().max_by_key(|(_, y)| *y).unwrap();
+  let east = *template.possible_doors_xy[&3].iter().min_by_key(|(_, y)| *y).unwrap();
+
+  let mut room = Room::new(template);
+  room.x = 3;
+  room.y = 2;
+  room.add_door(0, 1, 5, west, true);
+  room.add_door(0, 2, 3, east, true);
+  room
+}
+
+/// The details filled into the room, read back from the map's decoration layer
+fn details(map: &Map, room: &Room) -> Details {
+  (0..room.tiles.len() as i32)
+    .map(|idx| (idx % room.template.w, idx / room.template.w))
+    .filter(|(x, y)| room.tiles[xy_idx(*x, *y, room.template.w)] == 8)
+    .map(|(x, y)| {
+      let detail = match map.layers.decoration[xy_idx(room.x + x, room.y + y, map.width)] {
+        1 => Detail::Terrain,
+        2 => Detail::Hazard,
+        3 => Detail::Decor,
+        _ => Detail::Empty,
+      };
+      (x, y, detail)
+    })
+    .collect()
+}
+
+#[test]
+fn fill_keeps_protected_tiles_clear_and_doors_reachable() {
+  let physics = JumpPhysics::default();
+  let room = room();
+  let protected = interior::protected_tiles(&room);
+  let mut filled = 0;
+
+  for seed in 0..16 {
+    let mut map = Map::new(24, 16);
+    map.rooms.insert(0, room.clone());
+    interior::fill(&mut map, &physics, seed);
+
+    let details = details(&map, &room);
+    for (x, y, detail) in details.iter() {
+      if protected.contains(&(*x, *y)) {
+        assert_eq!(*detail, Detail::Empty, "seed {}: ({}, {})", seed, x, y);
+      }
+    }
+    assert!(interior::is_traversable(&room, &details, &physics), "seed {}", seed);
+
+    if details.iter().any(|(_, _, detail)| *detail != Detail::Empty) {
+      filled += 1;
+    }
+  }
+
+  // Most seeds should find a valid collapse rather than leave the room empty
+  assert!(filled > 8, "{} of 16 seeds filled", filled);
+}
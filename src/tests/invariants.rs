@@ -0,0 +1,137 @@
+use crate::{
+  common::xy_idx,
+  graph::MapGraph,
+  map::{room::RoomType, validation, Map},
+};
+use std::collections::{HashSet, VecDeque};
+
+/// Every invariant a generated map must hold, returning the first broken one
+pub fn check_all(map: &Map, map_graph: &MapGraph) -> Result<(), String> {
+  no_clash_tiles(map)?;
+  doors_are_mirrored(map)?;
+  doors_align(map)?;
+  rooms_in_bounds(map)?;
+  one_start_room(map)?;
+  boss_reachable(map, map_graph)?;
+  validator_agrees(map, map_graph)
+}
+
+pub fn no_clash_tiles(map: &Map) -> Result<(), String> {
+  match map.tiles.iter().position(|tile| *tile == 9) {
+    Some(idx) => Err(format!("clash tile at ({}, {})", idx as i32 % map.width, idx as i32 / map.width)),
+    None => Ok(()),
+  }
+}
+
+/// Each door connection has a matching connection back on the target room
+pub fn doors_are_mirrored(map: &Map) -> Result<(), String> {
+  for (node_idx, room) in map.rooms.iter() {
+    for door in room.doors_world() {
+      let target = map.rooms.get(&door.target).ok_or(format!(
+        "room {} has a door to room {}, which isn't on the map",
+        node_idx, door.target
+      ))?;
+
+      let is_mirrored = target
+        .doors_world()
+        .iter()
+        .any(|back| back.target == *node_idx && back.direction != door.direction && back.secret == door.secret);
+
+      if !is_mirrored {
+        return Err(format!("room {} connects to room {}, but not the other way", node_idx, door.target));
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// Connected doors face each other on neighbouring tiles, eg: a north door sits directly above a south door
+pub fn doors_align(map: &Map) -> Result<(), String> {
+  for (node_idx, room) in map.rooms.iter() {
+    for door in room.doors_world() {
+      let (facing, (x, y)) = door.facing();
+
+      let aligns = map.rooms[&door.target]
+        .doors_world()
+        .iter()
+        .any(|back| back.target == *node_idx && back.door_type == facing && (back.x, back.y) == (x, y));
+
+      if !aligns {
+        return Err(format!(
+          "door of room {} at ({}, {}) doesn't line up with a door of room {}",
+          node_idx, door.x, door.y, door.target
+        ));
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// No room tile lies off the map or wraps around its sides, and the map tiles under each room are that room's
+pub fn rooms_in_bounds(map: &Map) -> Result<(), String> {
+  for (node_idx, room) in map.rooms.iter() {
+    let bounds = room.bounds();
+    if bounds.x < 0 || bounds.y < 0 || bounds.x + bounds.w > map.width || bounds.y + bounds.h > map.height {
+      return Err(format!("room {} at ({}, {}) lies outside the map", node_idx, bounds.x, bounds.y));
+    }
+
+    for (idx, tile) in room.tiles.iter().enumerate() {
+      let (x, y) = (bounds.x + idx as i32 % bounds.w, bounds.y + idx as i32 / bounds.w);
+      if *tile != 0 && map.tiles[xy_idx(x, y, map.width)] != *tile {
+        return Err(format!("map tile at ({}, {}) doesn't match room {}", x, y, node_idx));
+      }
+    }
+  }
+
+  Ok(())
+}
+
+pub fn one_start_room(map: &Map) -> Result<(), String> {
+  let start_rooms = map.rooms.values().filter(|room| room.template.room_type == RoomType::Start).count();
+
+  match start_rooms {
+    1 => Ok(()),
+    count => Err(format!("{} start rooms", count)),
+  }
+}
+
+/// The boss room was placed, and can be walked to from the first node's room through connected doors
+pub fn boss_reachable(map: &Map, map_graph: &MapGraph) -> Result<(), String> {
+  let boss = match map.rooms.iter().find(|(_, room)| room.template.room_type == RoomType::Boss) {
+    Some((boss, _)) => *boss,
+    None => return Err("no boss room placed".to_string()),
+  };
+  let start = map_graph.nodes.first().unwrap().index();
+
+  let mut visited = HashSet::from([start]);
+  let mut queue = VecDeque::from([start]);
+
+  while let Some(node_idx) = queue.pop_front() {
+    if node_idx == boss {
+      return Ok(());
+    }
+
+    for door in map.rooms[&node_idx].doors_world() {
+      if !door.secret && visited.insert(door.target) {
+        queue.push_back(door.target);
+      }
+    }
+  }
+
+  Err(format!("boss room {} can't be reached from the start room {}", boss, start))
+}
+
+/// The runtime validator finds nothing wrong with a generated map, other than graph nodes that couldn't be placed
+pub fn validator_agrees(map: &Map, map_graph: &MapGraph) -> Result<(), String> {
+  let mut report = validation::validate(map, map_graph);
+  let summary = report.to_string();
+  report.unrealised_edges.clear();
+
+  if report.is_valid() {
+    Ok(())
+  } else {
+    Err(format!("validation failed: {}", summary))
+  }
+}
@@ -0,0 +1,98 @@
+use super::config;
+use crate::graph::{
+  directed_grid_graph, directed_path_graph, directed_wheel_graph,
+  layout::{self, Layout, LayoutConfig},
+  lollipop_graph, undirected_edges, GraphResult, MapGraph,
+};
+use crate::{generate_map, load_templates};
+
+fn laid_out(result: GraphResult, method: Layout) -> MapGraph {
+  let mut map_graph = MapGraph::new(result);
+  layout::apply(&mut map_graph, &LayoutConfig { method, edge_length: 10.0 });
+  map_graph
+}
+
+fn distance(map_graph: &MapGraph, a: usize, b: usize) -> f64 {
+  let ((ax, ay), (bx, by)) = (map_graph.positions[&a], map_graph.positions[&b]);
+  ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt()
+}
+
+#[test]
+fn layouts_are_scaled_to_the_edge_length() {
+  for method in [Layout::ForceDirected, Layout::Planar] {
+    let map_graph = laid_out(directed_wheel_graph::new(8, false, false), method);
+    let edges = undirected_edges(&map_graph.graph);
+    let mean = edges.iter().map(|(a, b)| distance(&map_graph, *a, *b)).sum::<f64>() / edges.len() as f64;
+
+    assert_eq!(map_graph.positions.len(), 8, "{:?}", method);
+    assert!((mean - 10.0).abs() < 1e-6, "{:?} mean edge length {}", method, mean);
+  }
+}
+
+#[test]
+fn auto_layout_is_planar_when_it_can_be() {
+  let planar = laid_out(directed_grid_graph::new(3, 4, false), Layout::Planar);
+  let auto = laid_out(directed_grid_graph::new(3, 4, false), Layout::Auto);
+  assert_eq!(auto.positions, planar.positions);
+
+  // The complete graph on five nodes can't be drawn without a crossing
+  let force_directed = laid_out(lollipop_graph::new(5, 0), Layout::ForceDirected);
+  let auto = laid_out(lollipop_graph::new(5, 0), Layout::Auto);
+  assert_eq!(auto.positions, force_directed.positions);
+}
+
+#[test]
+fn force_directed_layout_follows_the_graph() {
+  let first = laid_out(directed_grid_graph::new(3, 4, false), Layout::ForceDirected);
+  let second = laid_out(directed_grid_graph::new(3, 4, false), Layout::ForceDirected);
+  assert_eq!(first.positions, second.positions, "the same graph gets the same layout");
+
+  // A path straightens out, so its ends are the furthest apart
+  let path = laid_out(directed_path_graph::new(8, false), Layout::ForceDirected);
+  let ends = distance(&path, 0, 7);
+  for a in 0..8 {
+    for b in a + 1..8 {
+      assert!(distance(&path, a, b) <= ends + 1e-6, "{}-{} further apart than the ends", a, b);
+    }
+  }
+
+  // Neighbours on a grid sit closer together than nodes that aren't
+  let grid = first;
+  let neighbours = undirected_edges(&grid.graph);
+  for node in 0..12 {
+    let (near, far): (Vec<usize>, Vec<usize>) = (0..12)
+      .filter(|other| *other != node)
+      .partition(|other| neighbours.contains(&(node, *other)) || neighbours.contains(&(*other, node)));
+    let mean = |others: &[usize]| others.iter().map(|other| distance(&grid, node, *other)).sum::<f64>() / others.len() as f64;
+    assert!(mean(&near) < mean(&far), "node {}", node);
+  }
+}
+
+#[test]
+fn rooms_are_placed_towards_their_layout_position() {
+  // How far each pair of joined rooms is from the offset between their nodes' positions
+  let mut templates = load_templates(&config(0));
+  let mut mismatch = |map_graph: &MapGraph, laid_out: &MapGraph, seed: u64| -> f64 {
+    let map = generate_map(&mut map_graph.clone(), &mut templates, config(seed));
+    undirected_edges(&laid_out.graph)
+      .iter()
+      .filter_map(|(a, b)| {
+        let (ax, ay) = map.rooms.get(a)?.bounds().centre();
+        let (bx, by) = map.rooms.get(b)?.bounds().centre();
+        let ((pax, pay), (pbx, pby)) = (laid_out.positions[a], laid_out.positions[b]);
+        Some(((bx - ax) - (pbx - pax)).powi(2) + ((by - ay) - (pby - pay)).powi(2))
+      })
+      .map(f64::sqrt)
+      .sum()
+  };
+
+  let with_layout = laid_out(directed_grid_graph::new(3, 4, false), Layout::ForceDirected);
+  let without_layout = MapGraph::new(directed_grid_graph::new(3, 4, false));
+
+  let (mut guided, mut random) = (0.0, 0.0);
+  for seed in 0..6 {
+    guided += mismatch(&with_layout, &with_layout, seed);
+    random += mismatch(&without_layout, &with_layout, seed);
+  }
+  assert!(guided < random, "guided {:.1} vs random {:.1}", guided, random);
+}
@@ -0,0 +1,73 @@
+use crate::{
+  map::{
+    self,
+    room::Room,
+    room_shapes::{self, RoomShape},
+    Map,
+  },
+  minimap::{Edges, Minimap},
+};
+
+/// An 8x8 room and a 4x8 room side by side, joined through the east door of the first, two cells down
+fn map() -> Map {
+  let mut map = Map::new(12, 8);
+
+  let big = room_shapes::generate(RoomShape::Rectangle, 8, 8).unwrap();
+  let east = *big.possible_doors_xy[&3].iter().find(|(_, y)| *y == 6).unwrap();
+  let mut room = Room::new(big);
+  room.add_door(0, 1, 3, east, true);
+  map::add_or_update_room(&mut map, 0, room);
+
+  let small = room_shapes::generate(RoomShape::Rectangle, 4, 8).unwrap();
+  let west = *small.possible_doors_xy[&5].iter().find(|(_, y)| *y == 6).unwrap();
+  let mut room = Room::new(small);
+  room.x = 8;
+  room.add_door(1, 0, 5, west, false);
+  map::add_or_update_room(&mut map, 1, room);
+
+  map
+}
+
+/// North, east, south and west flags
+fn sides(edges: Edges) -> [bool; 4] {
+  [edges.north, edges.east, edges.south, edges.west]
+}
+
+#[test]
+fn cells_record_their_room() {
+  let minimap = Minimap::new(&map(), 4);
+  assert_eq!((minimap.width, minimap.height, minimap.cells.len()), (3, 2, 6));
+
+  let rooms: Vec<Option<usize>> = minimap.cells.iter().map(|cell| cell.room).collect();
+  assert_eq!(rooms, vec![Some(0), Some(0), Some(1), Some(0), Some(0), Some(1)]);
+  assert!(minimap.cells.iter().all(|cell| !cell.secret && !cell.visited));
+}
+
+#[test]
+fn cells_record_walls_and_doors() {
+  let minimap = Minimap::new(&map(), 4);
+  let cell = |x, y| minimap.cell(x, y).unwrap();
+
+  // Walls where the room ends, either at the map edge or the other room
+  assert_eq!(sides(cell(0, 0).walls), [true, false, false, true]);
+  assert_eq!(sides(cell(1, 0).walls), [true, true, false, false]);
+  assert_eq!(sides(cell(1, 1).walls), [false, true, true, false]);
+  assert_eq!(sides(cell(2, 1).walls), [false, true, true, true]);
+
+  // The door out of the big room and the door into the small one, on either side of the same edge
+  assert_eq!(sides(cell(1, 1).doors), [false, true, false, false]);
+  assert_eq!(sides(cell(2, 1).doors), [false, false, false, true]);
+  for (x, y) in [(0, 0), (1, 0), (2, 0), (0, 1)] {
+    assert_eq!(sides(cell(x, y).doors), [false; 4], "cell {:?}", (x, y));
+  }
+  assert!(minimap.to_ascii().contains("+--+--+--+\n|.. ..|..|\n+     +  +\n|.. .. ..|\n"));
+}
+
+#[test]
+fn visiting_a_room_marks_all_its_cells() {
+  let mut minimap = Minimap::new(&map(), 4);
+  minimap.visit_room(1);
+
+  let visited: Vec<bool> = minimap.cells.iter().map(|cell| cell.visited).collect();
+  assert_eq!(visited, vec![false, false, true, false, false, true]);
+}
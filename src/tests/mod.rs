@@ -0,0 +1,115 @@
+mod combination_cache;
+mod cyclic_dungeon;
+mod door_budget;
+mod graph_generators;
+mod grid;
+mod interior;
+mod invariants;
+mod layout;
+mod minimap;
+mod planarity;
+mod platforms;
+mod postprocess;
+mod room_shapes;
+mod save_points;
+mod secret_rooms;
+mod spawner;
+mod template_selection;
+mod traversal;
+
+use crate::{
+  generate_graph, generate_map, load_templates,
+  map::{self, room::Room, validation, Map},
+  Config,
+};
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+/// The default generator config without any exports
+fn config(seed: u64) -> Config {
+  Config {
+    width: 100,
+    height: 100,
+    combination_cache: None,
+    postprocess: Some(Default::default()),
+    layout: Some(Default::default()),
+    physics: Some(Default::default()),
+    save_points: Some(Default::default()),
+    secret_rooms: Some(2),
+    spawn_table: Some(Default::default()),
+    seed,
+    export_path: None,
+    minimap: None,
+  }
+}
+
+proptest! {
+  #![proptest_config(ProptestConfig::with_cases(32))]
+
+  #[test]
+  fn generated_maps_hold_invariants(seed in any::<u64>()) {
+    let mut templates = load_templates(&config(seed));
+    let mut map_graph = generate_graph(&config(seed), &templates);
+    let map = generate_map(&mut map_graph, &mut templates, config(seed));
+
+    if let Err(broken) = invariants::check_all(&map, &map_graph) {
+      prop_assert!(false, "seed {}: {}", seed, broken);
+    }
+  }
+}
+
+#[test]
+fn validator_reports_tampered_maps() {
+  let mut templates = load_templates(&config(1));
+  let mut map_graph = generate_graph(&config(1), &templates);
+  let map = generate_map(&mut map_graph, &mut templates, config(1));
+  // Rooms the crawler couldn't place leave some edges unrealised already
+  let untampered = validation::validate(&map, &map_graph);
+
+  let start = map_graph.nodes.first().unwrap().index();
+  let tampered = |tamper: &dyn Fn(&mut HashMap<usize, Room>)| {
+    let mut rooms = map.rooms.clone();
+    tamper(&mut rooms);
+
+    // Rebuild the map so its tiles and layers match the tampered rooms
+    let mut tampered = Map::new(map.width, map.height);
+    let mut node_idxs: Vec<usize> = rooms.keys().cloned().collect();
+    node_idxs.sort();
+    for node_idx in node_idxs {
+      map::add_or_update_room(&mut tampered, node_idx, rooms[&node_idx].clone());
+    }
+    validation::validate(&tampered, &map_graph)
+  };
+
+  // Forget the start room's connections, the rooms past it still point back at it
+  let report = tampered(&|rooms| rooms.get_mut(&start).unwrap().door_connections.clear());
+  assert!(!report.one_sided_connections.is_empty());
+  assert!(!report.dangling_doors.is_empty());
+  assert!(!report.unreachable_rooms.is_empty());
+
+  // Move a room on top of the start room
+  let (edge_a, edge_b) = map_graph
+    .graph
+    .edge_indices()
+    .filter_map(|edge| map_graph.graph.edge_endpoints(edge))
+    .map(|(a, b)| (a.index(), b.index()))
+    .find(|(a, b)| *a == start && !untampered.unrealised_edges.contains(&(*a, *b)))
+    .unwrap();
+  let report = tampered(&|rooms| {
+    let (x, y) = (rooms[&start].x, rooms[&start].y);
+    let moved = rooms.get_mut(&edge_b).unwrap();
+    (moved.x, moved.y) = (x, y);
+  });
+  assert!(report.overlapping_rooms.iter().any(|(_, moved)| *moved == edge_b), "{}", report);
+
+  // Take out the doors of one of the graph's edges, from both rooms
+  let report = tampered(&|rooms| {
+    for (node_idx, other) in [(edge_a, edge_b), (edge_b, edge_a)] {
+      for doors in rooms.get_mut(&node_idx).unwrap().door_connections.values_mut() {
+        doors.retain(|door| door.node_b_idx != other);
+      }
+    }
+  });
+  assert!(report.unrealised_edges.contains(&(edge_a, edge_b)));
+  assert_eq!(report.unrealised_edges.len(), untampered.unrealised_edges.len() + 1);
+}
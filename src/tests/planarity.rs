@@ -0,0 +1,128 @@
+use crate::graph::{
+  directed_grid_graph, directed_heavy_hex_graph, directed_hexagonal_lattice_graph, directed_path_graph, directed_wheel_graph,
+  generalized_petersen_graph, lollipop_graph, planarity, GraphResult, MapGraph,
+};
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+
+/// Complete bipartite graph K3,3, the smallest non-planar graph by edges
+fn utility_graph() -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let nodes: Vec<NodeIndex> = (0..6).map(|w| graph.add_node(w + 1)).collect();
+  for a in 0..3 {
+    for b in 3..6 {
+      graph.add_edge(nodes[a], nodes[b], 0);
+    }
+  }
+  (graph, nodes)
+}
+
+#[test]
+fn recognises_planar_graphs() {
+  let planar = [
+    ("path", directed_path_graph::new(8, false)),
+    ("grid", directed_grid_graph::new(4, 5, true)),
+    ("wheel", directed_wheel_graph::new(8, false, false)),
+    ("complete 4", lollipop_graph::new(4, 3)),
+    ("hexagonal lattice", directed_hexagonal_lattice_graph::new(3, 4, false)),
+    ("heavy hex", directed_heavy_hex_graph::new(5, false)),
+    ("prism", generalized_petersen_graph::new(6, 1)),
+  ];
+
+  for (name, result) in planar {
+    assert!(planarity::is_planar(&result.0), "{}", name);
+    let report = planarity::analyse(&MapGraph::new(result));
+    assert!(report.is_planar(), "{}: {}", name, report);
+  }
+}
+
+#[test]
+fn reports_portal_edges_for_non_planar_graphs() {
+  // Both are one edge away from planar
+  for (name, result) in [("complete 5", lollipop_graph::new(5, 0)), ("utility", utility_graph())] {
+    assert!(!planarity::is_planar(&result.0), "{}", name);
+
+    let report = planarity::analyse(&MapGraph::new(result.clone()));
+    assert_eq!(report.portal_edges.len(), 1, "{}: {}", name, report);
+
+    let (mut graph, _) = result;
+    let (a, b) = report.portal_edges[0];
+    let edge = graph.find_edge_undirected(NodeIndex::new(a), NodeIndex::new(b)).unwrap().0;
+    graph.remove_edge(edge);
+    assert!(planarity::is_planar(&graph), "{} without {}-{}", name, a, b);
+  }
+
+  let petersen = generalized_petersen_graph::new(5, 2);
+  assert!(!planarity::is_planar(&petersen.0));
+  let report = planarity::analyse(&MapGraph::new(petersen.clone()));
+  assert!(!report.is_planar());
+  assert!(report.to_string().starts_with("not planar"));
+
+  let (mut graph, _) = petersen;
+  for (a, b) in report.portal_edges.iter() {
+    let edge = graph.find_edge_undirected(NodeIndex::new(*a), NodeIndex::new(*b)).unwrap().0;
+    graph.remove_edge(edge);
+  }
+  assert!(planarity::is_planar(&graph));
+}
+
+#[test]
+fn tutte_positions_pin_the_outer_face() {
+  // The rim is the longest face, so it goes around the circle and the hub settles in the middle
+  let wheel = MapGraph::new(directed_wheel_graph::new(7, false, false));
+  let report = planarity::analyse(&wheel);
+
+  assert_eq!(report.positions.len(), 7);
+  let (hub_x, hub_y) = report.positions[&0];
+  assert!(hub_x.abs() < 1e-6 && hub_y.abs() < 1e-6, "hub at {:?}", (hub_x, hub_y));
+  for rim in 1..7 {
+    let (x, y) = report.positions[&rim];
+    assert!(((x * x + y * y).sqrt() - 1.0).abs() < 1e-9, "rim node {} at {:?}", rim, (x, y));
+  }
+
+  // Every node inside the outer face is the average of its neighbours
+  let grid = MapGraph::new(directed_grid_graph::new(4, 4, false));
+  let report = planarity::analyse(&grid);
+  for node in grid.nodes.iter() {
+    let (x, y) = report.positions[&node.index()];
+    if ((x * x + y * y).sqrt() - 1.0).abs() < 1e-9 {
+      continue;
+    }
+
+    let neighbours: Vec<_> = grid.graph.neighbors_undirected(*node).collect();
+    let average_x = neighbours.iter().map(|next| report.positions[&next.index()].0).sum::<f64>() / neighbours.len() as f64;
+    let average_y = neighbours.iter().map(|next| report.positions[&next.index()].1).sum::<f64>() / neighbours.len() as f64;
+    assert!(
+      (average_x - x).abs() < 1e-6 && (average_y - y).abs() < 1e-6,
+      "node {}",
+      node.index()
+    );
+  }
+}
+
+#[test]
+fn tutte_positions_spread_out_trees() {
+  let distinct = |report: &planarity::PlanarityReport| {
+    let positions: Vec<&(f64, f64)> = report.positions.values().collect();
+    for (i, a) in positions.iter().enumerate() {
+      for b in positions.iter().skip(i + 1) {
+        assert!((a.0 - b.0).abs() + (a.1 - b.1).abs() > 1e-6, "{:?} and {:?} share a position", a, b);
+      }
+    }
+  };
+
+  // Without a cycle there is no face to pin, so the path is laid out in a line
+  let path = MapGraph::new(directed_path_graph::new(8, false));
+  let report = planarity::analyse(&path);
+  assert_eq!(report.positions.len(), 8);
+  distinct(&report);
+
+  // The path hanging off the complete graph moves away from it, instead of settling onto the node it hangs from
+  let lollipop = MapGraph::new(lollipop_graph::new(4, 3));
+  let report = planarity::analyse(&lollipop);
+  assert_eq!(report.positions.len(), 7);
+  distinct(&report);
+  for node in 4..7 {
+    let (x, y) = report.positions[&node];
+    assert!((x * x + y * y).sqrt() > 1.0 + 1e-6, "path node {} at {:?}", node, (x, y));
+  }
+}
@@ -0,0 +1,101 @@
+use crate::{
+  common::XY,
+  decoration::platforms,
+  map::{
+    self,
+    room::Room,
+    room_shapes::{self, RoomShape},
+    room_templates::RoomTemplate,
+    Map,
+  },
+  traversal::{self, JumpPhysics},
+};
+
+/// A T room entered from the far end of its bar and the bottom of its stem, and a tall rectangle with doors in its
+/// roof, floor and one wall
+fn map() -> Map {
+  let mut map = Map::new(40, 20);
+
+  let t = room_shapes::generate(RoomShape::T, 9, 7).unwrap();
+  let north = *t.possible_doors_xy[&2].iter().max_by_key(|(x, _)| *x).unwrap();
+  let south = t.possible_doors_xy[&4][0];
+  let west = t.possible_doors_xy[&5][0];
+  add_room(&mut map, 0, t, &[(2, north), (4, south), (5, west)]);
+
+  let tall = room_shapes::generate(RoomShape::Rectangle, 6, 14).unwrap();
+  let roof = tall.possible_doors_xy[&2][0];
+  let floor = *tall.possible_doors_xy[&4].last().unwrap();
+  let wall = tall.possible_doors_xy[&3][2];
+  add_room(&mut map, 1, tall, &[(2, roof), (4, floor), (3, wall)]);
+
+  map
+}
+
+fn add_room(map: &mut Map, node_idx: usize, template: RoomTemplate, doors: &[(u8, XY)]) {
+  let mut room = Room::new(template);
+  room.x = 2 + 16 * node_idx as i32;
+  room.y = 2;
+  for (door_type, xy) in doors {
+    room.add_door(node_idx, 10 + node_idx, *door_type, *xy, true);
+  }
+  map::add_or_update_room(map, node_idx, room);
+}
+
+fn all_doors_reachable(room: &Room, physics: &JumpPhysics) -> bool {
+  let doors = traversal::used_doors(room);
+  doors.iter().all(|from| {
+    let reachable = traversal::reachable_tiles(&room.tiles, room.template.w, *from, physics);
+    doors.iter().all(|(_, to)| reachable.contains(to))
+  })
+}
+
+#[test]
+fn every_used_door_is_reachable_after_decorating() {
+  let physics = JumpPhysics::default();
+  assert!(map().rooms.values().any(|room| !all_doors_reachable(room, &physics)));
+
+  for seed in 0..16 {
+    let mut map = map();
+    assert_eq!(platforms::decorate(&mut map, &physics, seed), Vec::<usize>::new(), "seed {}", seed);
+    for (node_idx, room) in map.rooms.iter() {
+      assert!(all_doors_reachable(room, &physics), "seed {} room {}", seed, node_idx);
+    }
+  }
+}
+
+#[test]
+fn decoration_only_depends_on_the_seed() {
+  let physics = JumpPhysics::default();
+  let decorated = |seed| {
+    let mut map = map();
+    platforms::decorate(&mut map, &physics, seed);
+    map
+  };
+
+  let (first, second) = (decorated(3), decorated(3));
+  assert_eq!(first.tiles, second.tiles);
+  for (node_idx, room) in first.rooms.iter() {
+    assert_eq!(room.tiles, second.rooms[node_idx].tiles, "room {}", node_idx);
+  }
+}
+
+#[test]
+fn rooms_that_cant_be_fixed_are_reported() {
+  // Two chambers with a wall between them, nothing can join their doors
+  let tiles = vec![
+    1, 2, 1, 2, 1, //
+    1, 8, 1, 8, 1, //
+    1, 8, 1, 8, 1, //
+    1, 1, 1, 1, 1,
+  ];
+  let template = RoomTemplate {
+    w: 5,
+    h: 4,
+    tiles,
+    ..Default::default()
+  };
+
+  let mut map = Map::new(20, 10);
+  add_room(&mut map, 0, template, &[(2, (2, 1)), (2, (4, 1))]);
+  assert_eq!(platforms::decorate(&mut map, &JumpPhysics::default(), 0), vec![0]);
+}
@@ -0,0 +1,207 @@
+use super::config;
+use crate::{
+  generate_graph, generate_map,
+  graph::{
+    directed_cycle_graph, directed_gnp_random_graph, directed_path_graph, directed_star_graph, directed_wheel_graph,
+    postprocess::{self, PostprocessConfig},
+    GraphResult, MapGraph, NodeRole,
+  },
+  load_templates,
+  map::room::RoomType,
+  Config,
+};
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::HashMap;
+
+fn max_degree((graph, nodes): &GraphResult) -> usize {
+  nodes
+    .iter()
+    .map(|node| {
+      let mut neighbours: Vec<_> = graph.neighbors_undirected(*node).collect();
+      neighbours.sort();
+      neighbours.dedup();
+      neighbours.len()
+    })
+    .max()
+    .unwrap_or(0)
+}
+
+/// Two paths of 3 and 5 nodes and a lone node, with nothing between them
+fn three_components() -> GraphResult {
+  let mut graph = StableGraph::<usize, usize>::new();
+  let nodes: Vec<NodeIndex> = (0..9).map(|w| graph.add_node(w + 1)).collect();
+  for (a, b) in [(0, 1), (1, 2), (3, 4), (4, 5), (5, 6), (6, 7)] {
+    graph.add_edge(nodes[a], nodes[b], 0);
+  }
+  (graph, nodes)
+}
+
+#[test]
+fn removes_self_loops_and_duplicates() {
+  let (mut graph, nodes) = directed_path_graph::new(3, false);
+  graph.add_edge(nodes[0], nodes[0], 0);
+  graph.add_edge(nodes[0], nodes[1], 0);
+  graph.add_edge(nodes[0], nodes[1], 0);
+  graph.add_edge(nodes[1], nodes[0], 0);
+
+  let mut result = (graph, nodes);
+  postprocess::remove_self_loops_and_duplicates(&mut result);
+
+  // The reverse edge is a different edge, and stays
+  assert_eq!(result.0.edge_count(), 3);
+  assert!(result.0.find_edge(result.1[1], result.1[0]).is_some());
+}
+
+#[test]
+fn keeps_largest_component() {
+  let mut result = three_components();
+  postprocess::keep_largest_component(&mut result);
+
+  assert_eq!(result.1, (3..8).map(NodeIndex::new).collect::<Vec<_>>());
+  assert_eq!(result.0.node_count(), 5);
+  assert_eq!(result.0.edge_count(), 4);
+  assert_eq!(postprocess::components(&result).len(), 1);
+}
+
+#[test]
+fn connects_components() {
+  let mut result = three_components();
+  assert_eq!(postprocess::components(&result).len(), 3);
+
+  postprocess::connect_components(&mut result);
+
+  assert_eq!(postprocess::components(&result).len(), 1);
+  assert_eq!(result.0.node_count(), 9);
+  assert_eq!(result.0.edge_count(), 8);
+  // Joined through the ends of the paths, not their middles
+  assert_eq!(max_degree(&result), 2);
+}
+
+#[test]
+fn caps_degree() {
+  // Edges on the rim loop are removed outright
+  let mut wheel = directed_wheel_graph::new(9, false, false);
+  postprocess::cap_degree(&mut wheel, 3);
+  assert!(max_degree(&wheel) <= 3);
+  assert_eq!(postprocess::components(&wheel).len(), 1);
+
+  // A star has no loops, so spokes are moved onto each other
+  for cap in 2..5 {
+    let mut star = directed_star_graph::new(9, false, false);
+    postprocess::cap_degree(&mut star, cap);
+    assert!(max_degree(&star) <= cap, "cap {}", cap);
+    assert_eq!(star.0.edge_count(), 8, "still a tree, cap {}", cap);
+    assert_eq!(postprocess::components(&star).len(), 1, "cap {}", cap);
+  }
+
+  for seed in 0..20 {
+    let mut result = directed_gnp_random_graph::new(25, 0.3, &mut StdRng::seed_from_u64(seed));
+    postprocess::connect_components(&mut result);
+    postprocess::cap_degree(&mut result, 4);
+    assert!(max_degree(&result) <= 4, "seed {}", seed);
+    assert_eq!(postprocess::components(&result).len(), 1, "seed {}", seed);
+  }
+}
+
+#[test]
+fn makes_bidirectional() {
+  let mut result = directed_cycle_graph::new(5, false);
+  postprocess::make_bidirectional(&mut result);
+  assert_eq!(result.0.edge_count(), 10);
+
+  postprocess::make_bidirectional(&mut result);
+  assert_eq!(result.0.edge_count(), 10);
+}
+
+#[test]
+fn apply_updates_the_map_graph() {
+  let (graph, nodes) = three_components();
+  let mut map_graph = MapGraph {
+    graph,
+    nodes,
+    neighbour_map: HashMap::new(),
+    roles: HashMap::from([(1, NodeRole::new(&["shop"])), (4, NodeRole::new(&["save"]))]),
+    positions: HashMap::new(),
+  };
+  let config = PostprocessConfig {
+    keep_largest_component: true,
+    connect_components: true,
+    cap_degree: true,
+    bidirectional: true,
+  };
+
+  postprocess::apply(&mut map_graph, &config, 4);
+
+  assert_eq!(map_graph.nodes.len(), 5);
+  assert_eq!(
+    map_graph.roles.keys().collect::<Vec<_>>(),
+    [&4],
+    "roles of removed nodes are dropped"
+  );
+  assert_eq!(map_graph.graph.edge_count(), 8);
+  assert_eq!(map_graph.neighbour_map.len(), 5);
+  assert!(map_graph.neighbour_map.keys().all(|node| *node >= 3));
+}
+
+#[test]
+fn connects_every_component() {
+  for seed in 0..20 {
+    // Sparse enough to fall apart into many pieces
+    let mut result = directed_gnp_random_graph::new(30, 0.04, &mut StdRng::seed_from_u64(seed));
+    let (pieces, edges) = (postprocess::components(&result).len(), result.0.edge_count());
+
+    postprocess::connect_components(&mut result);
+    assert_eq!(postprocess::components(&result).len(), 1, "seed {}", seed);
+    assert_eq!(result.0.edge_count(), edges + pieces - 1, "one edge per component, seed {}", seed);
+  }
+}
+
+#[test]
+fn capping_degree_keeps_the_graph_connected() {
+  for cap in 2..5 {
+    for seed in 0..20 {
+      let mut result = directed_gnp_random_graph::new(20, 0.4, &mut StdRng::seed_from_u64(seed));
+      postprocess::connect_components(&mut result);
+      postprocess::cap_degree(&mut result, cap);
+      assert_eq!(postprocess::components(&result).len(), 1, "cap {} seed {}", cap, seed);
+      assert_eq!(result.1.len(), 20, "cap {} seed {}", cap, seed);
+    }
+  }
+}
+
+#[test]
+fn maps_generate_after_nodes_are_removed() {
+  let mut templates = load_templates(&config(0));
+
+  // Removing the smaller components leaves holes in the node indices, and takes the heaviest node with them
+  let (graph, nodes) = three_components();
+  let mut map_graph = MapGraph::new((graph, nodes));
+  let keep_largest = PostprocessConfig {
+    keep_largest_component: true,
+    ..Default::default()
+  };
+  postprocess::apply(&mut map_graph, &keep_largest, 4);
+
+  let map = generate_map(&mut map_graph, &mut templates, config(0));
+  assert!(map
+    .rooms
+    .keys()
+    .all(|idx| (3..8).contains(idx) || map_graph.roles[idx].tags == ["secret"]));
+  assert!(map.rooms.get(&7).is_some_and(|room| room.template.room_type == RoomType::Boss));
+
+  // The same for generated graphs, seed 55 loses part of its graph
+  for seed in [55, 56, 57] {
+    let config = Config {
+      postprocess: Some(keep_largest.clone()),
+      ..config(seed)
+    };
+    let mut map_graph = generate_graph(&config, &templates);
+    let map = generate_map(&mut map_graph, &mut templates, config);
+    assert!(
+      map.rooms.values().any(|room| room.template.room_type == RoomType::Boss),
+      "seed {}",
+      seed
+    );
+  }
+}
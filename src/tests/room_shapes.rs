@@ -0,0 +1,84 @@
+use crate::map::{
+  room_shapes::{self, RoomShape, RoomShapeGenerator},
+  room_templates::RoomTemplate,
+};
+
+const SHAPES: [RoomShape; 5] = [RoomShape::Rectangle, RoomShape::L, RoomShape::T, RoomShape::U, RoomShape::Plus];
+
+/// Tile at zero based coordinates, exterior outside the template
+fn tile(template: &RoomTemplate, x: i32, y: i32) -> u8 {
+  if x < 0 || y < 0 || x >= template.w || y >= template.h {
+    return 0;
+  }
+  template.tiles[(y * template.w + x) as usize]
+}
+
+/// Zero based step from a door to the exterior
+fn outwards(door_type: u8) -> (i32, i32) {
+  match door_type {
+    2 => (0, -1),
+    3 => (1, 0),
+    4 => (0, 1),
+    _ => (-1, 0),
+  }
+}
+
+#[test]
+fn shapes_fill_the_requested_size() {
+  for shape in SHAPES {
+    let template = room_shapes::generate(shape, 12, 10).unwrap();
+    assert_eq!((template.w, template.h, template.tiles.len()), (12, 10, 120));
+    assert!(template.tiles.contains(&8));
+    assert!(!template.tiles.contains(&9), "a door tile with no face");
+  }
+
+  // The outline matches the shape: the L's top right and the T's bottom corners are outside the room
+  let l = room_shapes::generate(RoomShape::L, 12, 10).unwrap();
+  assert_eq!((tile(&l, 0, 0), tile(&l, 11, 0), tile(&l, 11, 9)), (1, 0, 1));
+  let t = room_shapes::generate(RoomShape::T, 12, 10).unwrap();
+  assert_eq!((tile(&t, 0, 0), tile(&t, 0, 9), tile(&t, 11, 9)), (1, 0, 0));
+}
+
+#[test]
+fn shapes_too_small_are_skipped() {
+  assert!(room_shapes::generate(RoomShape::Rectangle, 2, 5).is_none());
+  assert!(room_shapes::generate(RoomShape::T, 6, 8).is_none());
+  assert!(room_shapes::generate(RoomShape::Plus, 7, 6).is_none());
+
+  // Only widths 7 and 8 are wide enough for a plus
+  let generator = RoomShapeGenerator::new(RoomShape::Plus, 2..=8, 7..=8);
+  let templates = generator.templates();
+  assert_eq!(templates.len(), 4);
+  assert!(templates.iter().all(|template| template.w >= 7 && template.h >= 7));
+}
+
+#[test]
+fn doors_face_the_exterior() {
+  for shape in SHAPES {
+    let template = room_shapes::generate(shape, 12, 10).unwrap();
+
+    // Every shape can be entered from all four sides
+    for door_type in 2..=5 {
+      let doors = &template.possible_doors_xy[&door_type];
+      assert!(!doors.is_empty(), "no {} doors", door_type);
+
+      for (x, y) in doors {
+        let (x, y) = (x - 1, y - 1);
+        let (dx, dy) = outwards(door_type);
+        assert_eq!(tile(&template, x, y), door_type);
+        assert_eq!(tile(&template, x + dx, y + dy), 0, "door {:?} isn't on the outside", (x, y));
+        assert_eq!(tile(&template, x - dx, y - dy), 8, "door {:?} doesn't lead in", (x, y));
+      }
+    }
+  }
+}
+
+#[test]
+fn shapes_with_arms_need_enough_doors() {
+  let min_doors = |shape| room_shapes::generate(shape, 12, 10).unwrap().min_doors;
+  assert_eq!(min_doors(RoomShape::Rectangle), 0);
+  assert_eq!(min_doors(RoomShape::L), 0);
+  assert_eq!(min_doors(RoomShape::T), 3);
+  assert_eq!(min_doors(RoomShape::U), 2);
+  assert_eq!(min_doors(RoomShape::Plus), 2);
+}
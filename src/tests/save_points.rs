@@ -0,0 +1,81 @@
+use super::config;
+use crate::{
+  generate_graph, generate_map,
+  graph::{
+    critical_path::{self, SavePointConfig},
+    directed_path_graph, MapGraph, NodeRole,
+  },
+  load_templates,
+  map::room::RoomType,
+};
+
+/// Each node's role tags and fallbacks, in node order
+fn roles(map_graph: &MapGraph) -> Vec<(usize, Vec<Vec<String>>)> {
+  let mut roles: Vec<(usize, Vec<Vec<String>>)> = map_graph.roles.iter().map(|(idx, role)| (*idx, role.tag_options())).collect();
+  roles.sort();
+  roles
+}
+
+/// Tag options as tag_options returns them, ending with any room
+fn tags(options: &[&[&str]]) -> Vec<Vec<String>> {
+  options
+    .iter()
+    .chain([&[][..]].iter())
+    .map(|tags| tags.iter().map(|tag| tag.to_string()).collect())
+    .collect()
+}
+
+#[test]
+fn saves_shops_and_teleports_take_turns() {
+  let mut map_graph = MapGraph::new(directed_path_graph::new(14, false));
+  critical_path::place_save_points(&mut map_graph, &SavePointConfig::default());
+
+  // A save every 4 rooms, a shop after every 2nd save, the 3rd save is a teleport. The boss room keeps no role
+  assert_eq!(
+    roles(&map_graph),
+    vec![
+      (4, tags(&[&["save"]])),
+      (8, tags(&[&["save"]])),
+      (9, tags(&[&["shop"]])),
+      (12, tags(&[&["teleport"], &["save"]])),
+    ]
+  );
+}
+
+#[test]
+fn save_points_step_around_existing_roles() {
+  let mut map_graph = MapGraph::new(directed_path_graph::new(10, false));
+  map_graph.set_role(4, NodeRole::new(&["arena"]));
+  critical_path::place_save_points(&mut map_graph, &SavePointConfig::default());
+
+  // The save moves back a room rather than replace the arena, and the rest follow on from it
+  assert_eq!(
+    roles(&map_graph),
+    vec![
+      (3, tags(&[&["save"]])),
+      (4, tags(&[&["arena"]])),
+      (7, tags(&[&["save"]])),
+      (8, tags(&[&["shop"]])),
+    ]
+  );
+}
+
+#[test]
+fn critical_path_ends_at_the_boss_room() {
+  let mut templates = load_templates(&config(0));
+
+  for seed in 3..11 {
+    let mut map_graph = generate_graph(&config(seed), &templates);
+    let map = generate_map(&mut map_graph, &mut templates, config(seed));
+
+    let path = critical_path::critical_path(&map_graph).unwrap();
+    assert_eq!(path.first(), map_graph.nodes.first().map(|node| node.index()).as_ref());
+
+    let boss = map
+      .rooms
+      .iter()
+      .find(|(_, room)| room.template.room_type == RoomType::Boss)
+      .map(|(idx, _)| *idx);
+    assert_eq!(boss.as_ref(), path.last(), "seed {}", seed);
+  }
+}
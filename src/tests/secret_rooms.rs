@@ -0,0 +1,120 @@
+use super::config;
+use crate::{
+  common::xy_idx,
+  generate_graph, generate_map,
+  graph::MapGraph,
+  load_templates,
+  map::{export, layers::SOLID, room::Room, room_templates::RoomTemplates, Map},
+  Config,
+};
+use petgraph::stable_graph::NodeIndex;
+
+fn generate(templates: &mut RoomTemplates, config: Config) -> (Map, MapGraph) {
+  let mut map_graph = generate_graph(&config, templates);
+  let map = generate_map(&mut map_graph, templates, config);
+  (map, map_graph)
+}
+
+fn secret_rooms(map: &Map) -> Vec<usize> {
+  let mut secret: Vec<usize> = map
+    .rooms
+    .iter()
+    .filter(|(_, room)| room.template.tags.contains(&"secret".to_string()))
+    .map(|(idx, _)| *idx)
+    .collect();
+  secret.sort();
+  secret
+}
+
+/// A room's own tile at map tile coordinates
+fn room_tile(room: &Room, x: i32, y: i32) -> u8 {
+  room.tiles[((y - room.y) * room.template.w + x - room.x) as usize]
+}
+
+#[test]
+fn secret_rooms_hang_off_the_graph() {
+  let mut templates = load_templates(&config(0));
+  for seed in 3..11 {
+    let (map, map_graph) = generate(&mut templates, config(seed));
+
+    for idx in secret_rooms(&map) {
+      let node = NodeIndex::new(idx);
+      assert!(map_graph.graph.contains_node(node), "seed {}: room {} has no node", seed, idx);
+      assert!(map_graph.roles[&idx].tags.contains(&"secret".to_string()));
+
+      // One edge, from the room the secret door is in
+      let hosts: Vec<usize> = map_graph.graph.neighbors_undirected(node).map(|host| host.index()).collect();
+      let doors = map.rooms[&idx].doors_world();
+      assert_eq!(hosts.len(), 1, "seed {}: room {}", seed, idx);
+      assert_eq!(doors.iter().map(|door| door.target).collect::<Vec<usize>>(), hosts);
+    }
+  }
+}
+
+#[test]
+fn secret_doors_are_walls_on_both_sides() {
+  let mut templates = load_templates(&config(0));
+  let mut checked = 0;
+
+  for seed in 3..11 {
+    let (map, _) = generate(&mut templates, config(seed));
+    let exported: serde_json::Value = serde_json::from_str(&export::to_json(&map).unwrap()).unwrap();
+
+    for idx in secret_rooms(&map) {
+      let door = map.rooms[&idx].doors_world()[0];
+      let host = &map.rooms[&door.target];
+      let (_, (host_x, host_y)) = door.facing();
+      assert!(door.secret);
+
+      // A secret door tile on each side, solid until it's broken
+      assert_eq!(room_tile(&map.rooms[&idx], door.x, door.y), 6, "seed {}", seed);
+      assert_eq!(room_tile(host, host_x, host_y), 6, "seed {}", seed);
+      for (x, y) in [(door.x, door.y), (host_x, host_y)] {
+        assert_eq!(map.layers.collision[xy_idx(x, y, map.width)], SOLID, "seed {}: {:?}", seed, (x, y));
+      }
+
+      // ..and the export lists both, so the engine knows which walls can break
+      let listed = |node: usize| {
+        exported["rooms"]
+          .as_array()
+          .unwrap()
+          .iter()
+          .find(|room| room["node"] == node)
+          .unwrap()["secret_doors"]
+          .clone()
+      };
+      assert_eq!(listed(idx), serde_json::json!([[door.x, door.y]]));
+      assert_eq!(listed(door.target), serde_json::json!([[host_x, host_y]]));
+      checked += 1;
+    }
+  }
+
+  assert!(checked > 0, "no secret rooms placed");
+}
+
+#[test]
+fn secret_room_count_is_a_cap() {
+  let mut templates = load_templates(&config(0));
+  let mut most = 0;
+
+  for seed in 3..7 {
+    let none = Config {
+      secret_rooms: None,
+      ..config(seed)
+    };
+    assert!(secret_rooms(&generate(&mut templates, none).0).is_empty());
+
+    for count in [0, 1, 3] {
+      let capped = Config {
+        secret_rooms: Some(count),
+        ..config(seed)
+      };
+      let placed = secret_rooms(&generate(&mut templates, capped).0).len();
+      assert!(placed <= count, "seed {}: {} placed for {}", seed, placed, count);
+      most += if count == 3 { placed } else { 0 };
+    }
+  }
+
+  // There are plenty of rooms to hide them behind, so the cap is what stops them
+  assert!(most > 4, "{} placed over 4 maps", most);
+}
@@ -0,0 +1,103 @@
+use crate::{
+  graph::MapGraph,
+  map::{
+    self,
+    room::Room,
+    room_shapes::{self, RoomShape},
+    Map,
+  },
+  spawner::{self, SpawnKind, SpawnTable},
+};
+use petgraph::stable_graph::StableGraph;
+
+/// A map with a rectangle room for each node weight side by side, with their lowest west and east doors in use
+fn map(weights: &[usize]) -> (Map, MapGraph) {
+  let mut map = Map::new(16 * weights.len() as i32 + 2, 12);
+  let mut graph = StableGraph::<usize, usize>::default();
+  let nodes = weights.iter().map(|weight| graph.add_node(*weight)).collect();
+
+  for node_idx in 0..weights.len() {
+    let template = room_shapes::generate(RoomShape::Rectangle, 14, 8).unwrap();
+    let west = *template.possible_doors_xy[&5].iter().max_by_key(|(_, y)| *y).unwrap();
+    let east = *template.possible_doors_xy[&3].iter().max_by_key(|(_, y)| *y).unwrap();
+
+    let mut room = Room::new(template);
+    room.x = 1 + 16 * node_idx as i32;
+    room.y = 2;
+    room.add_door(node_idx, node_idx + 1, 5, west, true);
+    room.add_door(node_idx, node_idx + 1, 3, east, true);
+    map::add_or_update_room(&mut map, node_idx, room);
+  }
+
+  (map, MapGraph::new((graph, nodes)))
+}
+
+fn enemies(tiers: u32, door_clearance: i32) -> SpawnTable {
+  SpawnTable {
+    kinds: vec![(SpawnKind::Enemy, 1)],
+    min_density: 1.0,
+    max_density: 1.0,
+    tiers,
+    door_clearance,
+    ..Default::default()
+  }
+}
+
+#[test]
+fn tiers_scale_with_node_weight() {
+  let (mut map, map_graph) = map(&[1, 100]);
+  spawner::spawn(&mut map, &map_graph, &enemies(5, 1), 7);
+
+  let tiers = |room: usize| {
+    map
+      .spawns
+      .iter()
+      .filter(|spawn| spawn.room == room)
+      .map(|spawn| spawn.tier)
+      .collect::<Vec<_>>()
+  };
+  let (light, heavy) = (tiers(0), tiers(1));
+  assert!(!light.is_empty() && !heavy.is_empty());
+
+  // The lightest room rolls around the lowest tier, the heaviest around the highest
+  assert!(light.iter().all(|tier| *tier <= 2), "{:?}", light);
+  assert!(heavy.iter().all(|tier| *tier == 5), "{:?}", heavy);
+}
+
+#[test]
+fn no_tiers_places_nothing() {
+  let (mut map, map_graph) = map(&[1, 100]);
+  spawner::spawn(&mut map, &map_graph, &enemies(0, 1), 7);
+
+  assert!(map.spawns.is_empty());
+  assert!(map.layers.entities.iter().all(|entity| *entity == 0));
+}
+
+#[test]
+fn spawns_avoid_door_approaches() {
+  for door_clearance in 0..=3 {
+    let (mut map, map_graph) = map(&[1, 2]);
+    spawner::spawn(&mut map, &map_graph, &enemies(3, door_clearance), 3);
+    assert!(!map.spawns.is_empty());
+
+    for spawn in map.spawns.iter() {
+      for door in map.rooms[&spawn.room].doors_world() {
+        let distance = (door.x - spawn.x).abs().max((door.y - spawn.y).abs());
+        assert!(distance > door_clearance, "{:?} is {} from a door", spawn, distance);
+      }
+    }
+
+    // Every other floor tile is used at full density, so the spawns go right up to the clearance
+    let closest = map
+      .spawns
+      .iter()
+      .flat_map(|spawn| {
+        map.rooms[&spawn.room]
+          .doors_world()
+          .into_iter()
+          .map(|door| (door.x - spawn.x).abs().max((door.y - spawn.y).abs()))
+      })
+      .min();
+    assert_eq!(closest, Some(door_clearance + 1));
+  }
+}
@@ -0,0 +1,125 @@
+use crate::crawler;
+use crate::graph::{directed_path_graph, MapGraph, NodeRole};
+use crate::map::{
+  room::Room,
+  room_templates::{self, RoomTemplate, RoomTemplates},
+  Map,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::HashMap;
+
+fn template(id: usize, weight: u32) -> RoomTemplate {
+  RoomTemplate {
+    id,
+    weight,
+    ..Default::default()
+  }
+}
+
+/// A map with rooms of the template placed at the nodes
+fn map_with(template: &RoomTemplate, nodes: &[usize]) -> Map {
+  let mut map = Map::new(50, 50);
+  for node in nodes {
+    map.rooms.insert(*node, Room::new(template.clone()));
+  }
+  map
+}
+
+#[test]
+fn max_per_map_limits_placed_rooms() {
+  let path = MapGraph::new(directed_path_graph::new(6, false));
+  let limited = RoomTemplate {
+    max_per_map: Some(2),
+    ..template(1, 10)
+  };
+  let within = |placed: &[usize], node| crawler::within_usage_limits(&limited, node, &map_with(&limited, placed), &path.neighbour_map);
+
+  assert!(within(&[0], 4));
+  assert!(!within(&[0, 2], 4));
+  // A node's own room doesn't count against it
+  assert!(within(&[0, 2], 2));
+  // Rooms of other templates don't count
+  assert!(crawler::within_usage_limits(
+    &limited,
+    4,
+    &map_with(&template(2, 10), &[0, 2]),
+    &path.neighbour_map
+  ));
+}
+
+#[test]
+fn min_repeat_distance_keeps_repeats_apart() {
+  let path = MapGraph::new(directed_path_graph::new(6, false));
+  let spaced = RoomTemplate {
+    min_repeat_distance: Some(3),
+    ..template(1, 10)
+  };
+  let within = |placed: &[usize], node| crawler::within_usage_limits(&spaced, node, &map_with(&spaced, placed), &path.neighbour_map);
+
+  assert!(within(&[], 1));
+  assert!(!within(&[0], 1));
+  assert!(!within(&[0], 2));
+  assert!(within(&[0], 3));
+  assert!(!within(&[0, 5], 3));
+}
+
+#[test]
+fn weighted_shuffle_favours_heavier_templates() {
+  let templates = RoomTemplates {
+    rooms: HashMap::from([(0, template(0, 1)), (1, template(1, 9)), (2, template(2, 0))]),
+  };
+  let mut rng = StdRng::seed_from_u64(7);
+
+  let mut heavy_first = 0;
+  for _ in 0..2000 {
+    let order = room_templates::weighted_shuffle(vec![0, 1, 2], &templates, &mut rng);
+    // Weight 0 templates are never picked
+    assert_eq!(order.len(), 2);
+    if order[0] == 1 {
+      heavy_first += 1;
+    }
+  }
+
+  // Weights 9 and 1 put the heavier template first nine times in ten
+  assert!((1700..1900).contains(&heavy_first), "{} of 2000", heavy_first);
+}
+
+fn tags(tags: &[&str]) -> Vec<String> {
+  tags.iter().map(|tag| tag.to_string()).collect()
+}
+
+#[test]
+fn templates_match_every_required_tag() {
+  let arena = RoomTemplate {
+    tags: tags(&["arena", "vertical"]),
+    ..Default::default()
+  };
+  assert!(arena.matches_tags(&[]));
+  assert!(arena.matches_tags(&tags(&["arena"])));
+  assert!(arena.matches_tags(&tags(&["vertical", "arena"])));
+  assert!(!arena.matches_tags(&tags(&["arena", "treasure"])));
+  assert!(!RoomTemplate::default().matches_tags(&tags(&["arena"])));
+}
+
+#[test]
+fn reserved_tags_are_only_matched_when_asked_for() {
+  let shop = RoomTemplate {
+    tags: tags(&["shop", "vertical"]),
+    ..Default::default()
+  };
+  assert!(!shop.matches_tags(&[]));
+  assert!(!shop.matches_tags(&tags(&["vertical"])));
+  assert!(shop.matches_tags(&tags(&["shop"])));
+}
+
+#[test]
+fn roles_try_their_tags_then_fallbacks_then_any_room() {
+  let role = NodeRole::new(&["key"])
+    .with_fallback(&["treasure"])
+    .with_fallback(&["arena", "vertical"]);
+  assert_eq!(
+    role.tag_options(),
+    vec![tags(&["key"]), tags(&["treasure"]), tags(&["arena", "vertical"]), vec![]]
+  );
+  assert_eq!(NodeRole::new(&["shop"]).tag_options(), vec![tags(&["shop"]), vec![]]);
+}
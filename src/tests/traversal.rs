@@ -0,0 +1,174 @@
+use crate::{
+  common::XY,
+  map::{room::Room, room_templates::RoomTemplate, Map},
+  traversal::{self, JumpPhysics, Passage},
+};
+use std::collections::HashSet;
+
+const PHYSICS: JumpPhysics = JumpPhysics {
+  jump_height: 2,
+  jump_width: 1,
+};
+
+/// A wall two tiles high between the doors, the most PHYSICS can jump over
+const LOW_WALL: [u8; 35] = [
+  1, 1, 1, 1, 1, 1, 1, //
+  1, 8, 8, 8, 8, 8, 1, //
+  1, 8, 8, 1, 8, 8, 1, //
+  5, 8, 8, 1, 8, 8, 3, //
+  1, 1, 1, 1, 1, 1, 1,
+];
+
+/// The same wall one tile higher
+const HIGH_WALL: [u8; 42] = [
+  1, 1, 1, 1, 1, 1, 1, //
+  1, 8, 8, 8, 8, 8, 1, //
+  1, 8, 8, 1, 8, 8, 1, //
+  1, 8, 8, 1, 8, 8, 1, //
+  5, 8, 8, 1, 8, 8, 3, //
+  1, 1, 1, 1, 1, 1, 1,
+];
+
+/// A ledge three tiles above the east door, which can be dropped from but not jumped back up to
+const LEDGE: [u8; 36] = [
+  1, 1, 1, 1, 1, 1, //
+  5, 8, 8, 8, 8, 1, //
+  1, 1, 1, 8, 8, 1, //
+  1, 8, 8, 8, 8, 1, //
+  1, 8, 8, 8, 8, 3, //
+  1, 1, 1, 1, 1, 1,
+];
+
+/// The ledge with its drop replaced by a ladder through the floor
+const LADDER: [u8; 36] = [
+  1, 1, 1, 1, 1, 1, //
+  5, 8, 8, 8, 8, 1, //
+  1, 1, 1, 1, 11, 1, //
+  1, 8, 8, 8, 11, 1, //
+  1, 8, 8, 8, 11, 3, //
+  1, 1, 1, 1, 1, 1,
+];
+
+/// A ladder shaft from a north door straight down to a south door
+const SHAFT: [u8; 15] = [
+  1, 2, 1, //
+  1, 11, 1, //
+  1, 11, 1, //
+  1, 11, 1, //
+  1, 4, 1,
+];
+
+/// A room of hand drawn tiles, with the doors at `used` connected
+fn room(tiles: &[u8], width: i32, used: &[XY]) -> Room {
+  let mut room = Room::new(RoomTemplate {
+    w: width,
+    h: tiles.len() as i32 / width,
+    tiles: tiles.to_vec(),
+    ..Default::default()
+  });
+
+  for (node, xy) in used.iter().enumerate() {
+    room.add_door(0, node + 1, door_type(tiles, width, *xy), *xy, true);
+  }
+  room
+}
+
+fn door_type(tiles: &[u8], width: i32, (x, y): XY) -> u8 {
+  tiles[((y - 1) * width + x - 1) as usize]
+}
+
+/// Every tile that isn't wall, in door coordinates starting at 1
+fn open_tiles(tiles: &[u8], width: i32) -> HashSet<XY> {
+  (0..tiles.len() as i32)
+    .filter(|idx| tiles[*idx as usize] > 1)
+    .map(|idx| (idx % width + 1, idx / width + 1))
+    .collect()
+}
+
+fn reachable(room: &Room, door: XY) -> HashSet<XY> {
+  let door_type = door_type(&room.template.tiles, room.template.w, door);
+  traversal::reachable_tiles(&room.tiles, room.template.w, (door_type, door), &PHYSICS)
+}
+
+#[test]
+fn jumps_over_a_wall_at_the_jump_height() {
+  let (west, east) = ((1, 4), (7, 4));
+
+  let both = room(&LOW_WALL, 7, &[west, east]);
+  assert_eq!(reachable(&both, west), open_tiles(&LOW_WALL, 7));
+  assert_eq!(reachable(&both, east), open_tiles(&LOW_WALL, 7));
+
+  let room = room(&LOW_WALL, 7, &[west]);
+  assert!(traversal::can_connect_door(&room, (3, east), &PHYSICS));
+}
+
+#[test]
+fn cannot_jump_a_wall_above_the_jump_height() {
+  let (west, east) = ((1, 5), (7, 5));
+
+  // Only as high as the jump from the floor, and not over the wall
+  let west_side = HashSet::from([(1, 5), (2, 5), (3, 5), (2, 4), (3, 4), (2, 3), (3, 3)]);
+  let both = room(&HIGH_WALL, 7, &[west, east]);
+  assert_eq!(reachable(&both, west), west_side);
+  assert!(!reachable(&both, east).contains(&west));
+
+  let room = room(&HIGH_WALL, 7, &[west]);
+  assert!(!traversal::can_connect_door(&room, (3, east), &PHYSICS));
+}
+
+#[test]
+fn drops_from_a_ledge_one_way() {
+  let (west, east) = ((1, 2), (6, 5));
+
+  let both = room(&LEDGE, 6, &[west, east]);
+  // Walking off the ledge falls straight down, so the air past the drop is never reached
+  let mut from_ledge = open_tiles(&LEDGE, 6);
+  from_ledge.remove(&(5, 2));
+  assert_eq!(reachable(&both, west), from_ledge);
+  let below: HashSet<XY> = open_tiles(&LEDGE, 6).into_iter().filter(|(_, y)| *y > 2).collect();
+  assert_eq!(reachable(&both, east), below);
+
+  // Reachable one way is enough to connect
+  let room = room(&LEDGE, 6, &[west]);
+  assert!(traversal::can_connect_door(&room, (3, east), &PHYSICS));
+
+  let mut map = Map::new(6, 6);
+  map.rooms.insert(0, both);
+  traversal::annotate_rooms(&mut map, &PHYSICS);
+
+  let links = &map.rooms[&0].door_links;
+  assert_eq!(links.len(), 1);
+  assert_eq!((links[0].from, links[0].to, links[0].passage), (west, east, Passage::Drop));
+}
+
+#[test]
+fn climbs_a_ladder_both_ways() {
+  let (west, east) = ((1, 2), (6, 5));
+
+  let both = room(&LADDER, 6, &[west, east]);
+  assert_eq!(reachable(&both, west), open_tiles(&LADDER, 6));
+  assert_eq!(reachable(&both, east), open_tiles(&LADDER, 6));
+
+  let room = room(&LADDER, 6, &[east]);
+  assert!(traversal::can_connect_door(&room, (5, west), &PHYSICS));
+
+  let mut map = Map::new(6, 6);
+  map.rooms.insert(0, both);
+  traversal::annotate_rooms(&mut map, &PHYSICS);
+
+  let links = &map.rooms[&0].door_links;
+  assert_eq!(links.len(), 1);
+  assert_eq!(links[0].passage, Passage::TwoWay);
+}
+
+#[test]
+fn climbs_off_the_bottom_of_a_ladder() {
+  let (north, south) = ((2, 1), (2, 5));
+
+  let both = room(&SHAFT, 3, &[north, south]);
+  assert_eq!(reachable(&both, north), open_tiles(&SHAFT, 3));
+  assert_eq!(reachable(&both, south), open_tiles(&SHAFT, 3));
+
+  let room = room(&SHAFT, 3, &[north]);
+  assert!(traversal::can_connect_door(&room, (4, south), &PHYSICS));
+}
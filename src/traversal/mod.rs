@@ -0,0 +1,213 @@
+use super::{
+  common::XY,
+  map::{room::Room, Map},
+};
+use std::collections::{HashSet, VecDeque};
+
+/// How far the player can jump, in tiles
+#[derive(Clone, Copy)]
+pub struct JumpPhysics {
+  pub jump_height: i32,
+  pub jump_width: i32,
+}
+
+impl Default for JumpPhysics {
+  fn default() -> JumpPhysics {
+    JumpPhysics {
+      jump_height: 4,
+      jump_width: 3,
+    }
+  }
+}
+
+/// How the player can move between two doors of a room
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Passage {
+  /// Reachable in both directions
+  TwoWay,
+  /// Only reachable from the first door, eg: over a ledge too high to climb back
+  OneWay,
+  /// A one way passage where the player falls down to the second door
+  Drop,
+}
+
+/// Annotation of how two used doors of a room connect
+#[derive(Clone, Debug)]
+pub struct DoorLink {
+  /// XY Coordinates of the door the player leaves from
+  pub from: XY,
+  /// XY Coordinates of the door the player arrives at
+  pub to: XY,
+  pub passage: Passage,
+}
+
+/// A used door of a room: door type (2-5) and its XY coordinates, starting at 1
+pub type Door = (u8, XY);
+
+/// Every used door (7) and secret door (6) of a room, with the door type it was placed on
+pub fn used_doors(room: &Room) -> Vec<Door> {
+  room
+    .tiles
+    .iter()
+    .enumerate()
+    .filter(|(_, tile)| matches!(**tile, 6 | 7))
+    .map(|(idx, _)| {
+      let x = idx as i32 % room.template.w;
+      let y = idx as i32 / room.template.w;
+      (room.template.tiles[idx], (x + 1, y + 1))
+    })
+    .collect()
+}
+
+/// Annotate how each pair of the room's used doors can be travelled between. Pairs with no way through either
+/// direction are left out
+pub fn door_links(room: &Room, physics: &JumpPhysics) -> Vec<DoorLink> {
+  let doors = used_doors(room);
+  let reachable: Vec<HashSet<XY>> = doors
+    .iter()
+    .map(|door| reachable_tiles(&room.tiles, room.template.w, *door, physics))
+    .collect();
+
+  let mut links = vec![];
+
+  for (a, (_, door_a_xy)) in doors.iter().enumerate() {
+    for (b, (_, door_b_xy)) in doors.iter().enumerate() {
+      if a == b {
+        continue;
+      }
+
+      let a_to_b = reachable[a].contains(door_b_xy);
+      let b_to_a = reachable[b].contains(door_a_xy);
+
+      let passage = match (a_to_b, b_to_a) {
+        // Only add two way links once
+        (true, true) if a < b => Passage::TwoWay,
+        (true, false) if door_a_xy.1 < door_b_xy.1 => Passage::Drop,
+        (true, false) => Passage::OneWay,
+        _ => continue,
+      };
+
+      links.push(DoorLink {
+        from: *door_a_xy,
+        to: *door_b_xy,
+        passage,
+      });
+    }
+  }
+
+  links
+}
+
+/// Annotate every placed room with how its doors can be travelled between
+pub fn annotate_rooms(map: &mut Map, physics: &JumpPhysics) {
+  for room in map.rooms.values_mut() {
+    room.door_links = door_links(room, physics);
+  }
+}
+
+/// Check a new door can be travelled to or from every door the room already uses
+pub fn can_connect_door(room: &Room, door: Door, physics: &JumpPhysics) -> bool {
+  let (_, door_xy) = door;
+  let mut tiles = room.tiles.clone();
+  tiles[((door_xy.1 - 1) * room.template.w + door_xy.0 - 1) as usize] = 7;
+
+  let from_new_door = reachable_tiles(&tiles, room.template.w, door, physics);
+
+  used_doors(room)
+    .iter()
+    .all(|existing| from_new_door.contains(&existing.1) || reachable_tiles(&tiles, room.template.w, *existing, physics).contains(&door_xy))
+}
+
+/// Every tile the player can reach entering the room from a door, following gravity.
+/// Coordinates start at 1, the same as door coordinates
+pub fn reachable_tiles(tiles: &[u8], width: i32, door: Door, physics: &JumpPhysics) -> HashSet<XY> {
+  let height = tiles.len() as i32 / width;
+
+  let tile_at = |x: i32, y: i32| -> u8 {
+    if x < 0 || y < 0 || x >= width || y >= height {
+      return 0;
+    }
+    tiles[(y * width + x) as usize]
+  };
+
+  // 6 - secret door, open once broken
+  // 7 - used door
+  // 8 - interior space
+  // 11 - ladder
+  // 12 - one way platform, can be jumped through from below
+  let is_open = |x: i32, y: i32| -> bool { matches!(tile_at(x, y), 6 | 7 | 8 | 11 | 12) };
+
+  let mut reached: HashSet<XY> = HashSet::new();
+  let mut landed: HashSet<XY> = HashSet::new();
+  let mut queue: VecDeque<XY> = VecDeque::new();
+
+  // Fall from a tile until landing on something solid, a one way platform or grabbing a ladder
+  let fall = |x: i32, mut y: i32, reached: &mut HashSet<XY>| -> XY {
+    reached.insert((x, y));
+    while is_open(x, y + 1) && tile_at(x, y + 1) != 12 && tile_at(x, y) != 11 {
+      y += 1;
+      reached.insert((x, y));
+    }
+    (x, y)
+  };
+
+  let (door_type, (door_x, door_y)) = door;
+  let (start_x, start_y) = (door_x - 1, door_y - 1);
+
+  let start = match door_type {
+    // Jumping up through a south door, the player has the room below to push off from
+    4 => {
+      reached.insert((start_x, start_y));
+      (start_x, start_y)
+    }
+    _ => fall(start_x, start_y, &mut reached),
+  };
+
+  landed.insert(start);
+  queue.push_back(start);
+
+  while let Some((x, y)) = queue.pop_front() {
+    let mut next: Vec<XY> = vec![];
+
+    // Walk left and right, falling off any ledge
+    for dx in [-1, 1] {
+      if is_open(x + dx, y) {
+        next.push(fall(x + dx, y, &mut reached));
+      }
+    }
+
+    // Climb up and down ladders, letting go at the bottom, or drop through a one way platform
+    if tile_at(x, y) == 11 && is_open(x, y - 1) {
+      next.push(fall(x, y - 1, &mut reached));
+    }
+    if matches!(tile_at(x, y + 1), 11 | 12) || (tile_at(x, y) == 11 && is_open(x, y + 1)) {
+      next.push(fall(x, y + 1, &mut reached));
+    }
+
+    // Jump straight up to each height, then drift sideways at that height
+    for dy in 1..=physics.jump_height {
+      if !is_open(x, y - dy) {
+        break;
+      }
+      next.push(fall(x, y - dy, &mut reached));
+
+      for direction in [-1, 1] {
+        for dx in 1..=physics.jump_width {
+          let drift_x = x + dx * direction;
+          if !is_open(drift_x, y - dy) {
+            break;
+          }
+          next.push(fall(drift_x, y - dy, &mut reached));
+        }
+      }
+    }
+
+    for landing in next {
+      if landed.insert(landing) {
+        queue.push_back(landing);
+      }
+    }
+  }
+
+  reached.iter().map(|(x, y)| (x + 1, y + 1)).collect()
+}